@@ -0,0 +1,156 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::layergen::layer_number;
+use crate::stats::Stats;
+
+const TOAST_TIME: f32 = 3.0; // seconds each unlock stays on screen
+const TOAST_WIDTH: i32 = 300;
+const TOAST_HEIGHT: i32 = 50;
+const FONT_SIZE: i32 = 20;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstBite,
+    Sharpshooter,
+    Pendulum,
+    LongHaul,
+    OffTheGround,
+    AboveTheClouds,
+    IntoTheVoid,
+    GravityWins,
+    NightOwl,
+    SpeedDemon,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 10] = [
+        Achievement::FirstBite,
+        Achievement::Sharpshooter,
+        Achievement::Pendulum,
+        Achievement::LongHaul,
+        Achievement::OffTheGround,
+        Achievement::AboveTheClouds,
+        Achievement::IntoTheVoid,
+        Achievement::GravityWins,
+        Achievement::NightOwl,
+        Achievement::SpeedDemon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::FirstBite => "First bite",
+            Achievement::Sharpshooter => "Sharpshooter",
+            Achievement::Pendulum => "Pendulum",
+            Achievement::LongHaul => "Long haul",
+            Achievement::OffTheGround => "Off the ground",
+            Achievement::AboveTheClouds => "Above the clouds",
+            Achievement::IntoTheVoid => "Into the void",
+            Achievement::GravityWins => "Gravity wins",
+            Achievement::NightOwl => "Night owl",
+            Achievement::SpeedDemon => "Speed demon",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstBite => "Hook onto something",
+            Achievement::Sharpshooter => "Fire 50 hooks with 80% hitting",
+            Achievement::Pendulum => "Swing 10000 units",
+            Achievement::LongHaul => "Swing 100000 units",
+            Achievement::OffTheGround => "Climb 10 layers",
+            Achievement::AboveTheClouds => "Reach the sky",
+            Achievement::IntoTheVoid => "Reach space",
+            Achievement::GravityWins => "Fall 50 times",
+            Achievement::NightOwl => "Spend 10 minutes in the dark",
+            Achievement::SpeedDemon => "Move at 700 units per second",
+        }
+    }
+
+    pub fn earned(&self, stats: &Stats) -> bool {
+        match self {
+            Achievement::FirstBite => stats.hooks_hit >= 1,
+            Achievement::Sharpshooter => stats.hooks_fired >= 50 && stats.accuracy().unwrap_or(0.0) >= 0.8,
+            Achievement::Pendulum => stats.distance_swung >= 10000.0,
+            Achievement::LongHaul => stats.distance_swung >= 100000.0,
+            Achievement::OffTheGround => stats.highest_layer >= 10,
+            // biome bands start at these heights, y is negative going up
+            Achievement::AboveTheClouds => stats.highest_layer >= layer_number(-3500.0),
+            Achievement::IntoTheVoid => stats.highest_layer >= layer_number(-6000.0),
+            Achievement::GravityWins => stats.falls >= 50,
+            Achievement::NightOwl => stats.time_in_darkness >= 600.0,
+            Achievement::SpeedDemon => stats.max_speed >= 700.0,
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    pub fn has(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    // unlocks everything the stats allow, returns what is new
+    pub fn unlock_from(&mut self, stats: &Stats) -> Vec<Achievement> {
+        let new: Vec<Achievement> =
+            Achievement::ALL.iter().copied().filter(|a| !self.has(*a) && a.earned(stats)).collect();
+        self.unlocked.extend(new.iter().copied());
+        new
+    }
+}
+
+struct Toast {
+    title: String,
+    text: String,
+    time_left: f32,
+}
+
+// unlock notices, newest at the bottom, each one fades out on its own
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self { toasts: Vec::new() }
+    }
+
+    pub fn unlocked(&mut self, achievement: Achievement) {
+        self.show(format!("Achievement: {}", achievement.name()), achievement.description());
+    }
+
+    pub fn show(&mut self, title: String, text: &str) {
+        self.toasts.push(Toast { title, text: text.to_string(), time_left: TOAST_TIME });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for toast in self.toasts.iter_mut() {
+            toast.time_left -= delta_time;
+        }
+        self.toasts.retain(|toast| toast.time_left > 0.0);
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, screen_size: Vector2) {
+        let x = (screen_size.x as i32 - TOAST_WIDTH) / 2;
+        for (i, toast) in self.toasts.iter().enumerate() {
+            let y = 10 + i as i32 * (TOAST_HEIGHT + 5);
+            // fade over the last half second
+            let alpha = (toast.time_left / 0.5).min(1.0);
+            d.draw_rectangle(x, y, TOAST_WIDTH, TOAST_HEIGHT, Color::BLACK.alpha(0.8 * alpha));
+            d.draw_rectangle_lines(x, y, TOAST_WIDTH, TOAST_HEIGHT, Color::GOLD.alpha(alpha));
+            d.draw_text(&toast.title, x + 10, y + 6, FONT_SIZE, Color::GOLD.alpha(alpha));
+            d.draw_text(&toast.text, x + 10, y + 30, 14, Color::LIGHTGRAY.alpha(alpha));
+        }
+    }
+}
+
+impl Default for Toasts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,329 @@
+use std::f32::consts::TAU;
+use std::path::Path;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use raylib::prelude::*;
+
+use crate::biome::{Biome, blend_at};
+use crate::events::GameEvent;
+use crate::progress::height_of;
+use crate::settings::Settings;
+
+pub const SAMPLE_RATE: u32 = 22050;
+pub const AUDIO_DIR: &str = "assets/audio"; // <name>.wav here replaces the generated sound
+const HEARING_DISTANCE: f32 = 900.0; // positional sounds are silent this far from the listener
+const MUSIC_FADE: f32 = 2.0; // seconds for one track to fully replace another
+const MUSIC_LENGTH: f32 = 8.0; // generated tracks loop after this many seconds
+const LANDING_SPEED: f32 = 150.0; // softer landings are silent
+
+// the discriminant is the sound's slot, keep ALL in the same order
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[repr(usize)]
+pub enum Sfx {
+    HookFire,
+    HookHit,
+    RopeSnap,
+    Jump,
+    Land,
+    Respawn,
+    Pickup,
+    Caught,
+}
+
+impl Sfx {
+    pub const ALL: [Sfx; 8] = [
+        Sfx::HookFire,
+        Sfx::HookHit,
+        Sfx::RopeSnap,
+        Sfx::Jump,
+        Sfx::Land,
+        Sfx::Respawn,
+        Sfx::Pickup,
+        Sfx::Caught,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sfx::HookFire => "hook_fire",
+            Sfx::HookHit => "hook_hit",
+            Sfx::RopeSnap => "rope_snap",
+            Sfx::Jump => "jump",
+            Sfx::Land => "land",
+            Sfx::Respawn => "respawn",
+            Sfx::Pickup => "pickup",
+            Sfx::Caught => "caught",
+        }
+    }
+
+    // small synthesized effects so the game isn't silent without sound files
+    pub fn samples(&self) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::seed_from_u64(*self as u64);
+        match self {
+            Sfx::HookFire => synth(0.15, |t| sweep(t, 900.0, 300.0, 0.15).sin() * decay(t, 20.0) * 0.6),
+            Sfx::HookHit => synth(0.2, |t| {
+                let clank = (square(t * 220.0) * 0.5 + (t * 1310.0 * TAU).sin() * 0.5) * decay(t, 25.0);
+                clank + rng.random_range(-1.0..1.0) * decay(t, 60.0) * 0.4
+            }),
+            Sfx::RopeSnap => synth(0.12, |t| rng.random_range(-1.0..1.0) * decay(t, 35.0) * 0.7),
+            Sfx::Jump => synth(0.15, |t| square(sweep(t, 300.0, 650.0, 0.15) / TAU) * decay(t, 12.0) * 0.3),
+            Sfx::Land => synth(0.2, |t| {
+                sweep(t, 90.0, 45.0, 0.2).sin() * decay(t, 18.0) * 0.9 + rng.random_range(-1.0..1.0) * decay(t, 50.0) * 0.2
+            }),
+            Sfx::Respawn => synth(0.6, |t| {
+                // rising arpeggio
+                let note = [0.0, 4.0, 7.0, 12.0][((t / 0.15) as usize).min(3)];
+                let frequency = 440.0 * 2f32.powf(note / 12.0);
+                (t * frequency * TAU).sin() * decay(t % 0.15, 8.0) * 0.4
+            }),
+            // two quick chimes a fifth apart
+            Sfx::Pickup => synth(0.25, |t| {
+                let frequency = if t < 0.08 { 988.0 } else { 1480.0 };
+                (t * frequency * TAU).sin() * decay(t % 0.08, 15.0) * 0.35
+            }),
+            // low rasping snarl
+            Sfx::Caught => synth(0.4, |t| {
+                let growl = square(sweep(t, 120.0, 60.0, 0.4) / TAU) * 0.5;
+                (growl + rng.random_range(-1.0..1.0) * 0.4) * decay(t, 6.0) * 0.6
+            }),
+        }
+    }
+}
+
+// one looping track per biome, a drone under a slow arpeggio
+pub fn music_samples(biome: Biome) -> Vec<f32> {
+    let (root, notes, notes_per_second): (f32, &[f32], f32) = match biome {
+        Biome::Cave => (55.0, &[0.0, 3.0, 7.0, 10.0], 1.0),
+        Biome::Industrial => (65.0, &[0.0, 0.0, 7.0, 6.0], 4.0),
+        Biome::Sky => (110.0, &[0.0, 4.0, 7.0, 9.0, 12.0, 9.0, 7.0, 4.0], 2.0),
+        Biome::Space => (41.0, &[0.0, 7.0, 14.0, 19.0], 0.5),
+    };
+    // whole cycles per loop so the seam doesn't click
+    let looped = |frequency: f32| (frequency * MUSIC_LENGTH).round() / MUSIC_LENGTH;
+    synth(MUSIC_LENGTH, |t| {
+        let swell = 0.75 + 0.25 * (t / MUSIC_LENGTH * TAU).sin();
+        let drone = (t * looped(root) * TAU).sin() + 0.5 * (t * looped(root * 1.5) * TAU).sin();
+        let step = t * notes_per_second;
+        let note = notes[step as usize % notes.len()];
+        let frequency = looped(root * 4.0 * 2f32.powf(note / 12.0));
+        let pluck = (t * frequency * TAU).sin() * decay(step.fract() / notes_per_second, 4.0);
+        (drone * 0.2 * swell + pluck * 0.25) * 0.8
+    })
+}
+
+fn synth(seconds: f32, mut sample: impl FnMut(f32) -> f32) -> Vec<f32> {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..count).map(|i| sample(i as f32 / SAMPLE_RATE as f32).clamp(-1.0, 1.0)).collect()
+}
+
+fn decay(t: f32, rate: f32) -> f32 {
+    (-t * rate).exp()
+}
+
+fn square(phase: f32) -> f32 {
+    if phase.fract() < 0.5 { 1.0 } else { -1.0 }
+}
+
+// phase of a sine sliding linearly between two frequencies
+fn sweep(t: f32, from: f32, to: f32, length: f32) -> f32 {
+    TAU * (from * t + (to - from) * t * t / (2.0 * length))
+}
+
+// 16 bit mono pcm, what raylib loads from memory
+pub fn wav_bytes(samples: &[f32]) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // pcm
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    bytes
+}
+
+// a sound asked for this frame, volume and pan already worked out
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Cue {
+    pub sfx: Sfx,
+    pub volume: f32,
+    pub pan: f32, // -1 left, 1 right
+}
+
+// decides what is heard and how loud, without touching the audio device
+pub struct Mixer {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub listener: Vector2,
+    pub cues: Vec<Cue>,
+    pub music: [f32; 4], // fade of each biome's track, in Biome::ALL order
+}
+
+impl Mixer {
+    pub fn new(settings: &Settings) -> Self {
+        let mut mixer = Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            listener: Vector2::zero(),
+            cues: Vec::new(),
+            music: [0.0; 4],
+        };
+        mixer.set_volumes(settings);
+        mixer
+    }
+
+    pub fn set_volumes(&mut self, settings: &Settings) {
+        self.master_volume = settings.master_volume;
+        self.music_volume = settings.music_volume;
+        self.sfx_volume = settings.sfx_volume;
+    }
+
+    pub fn play(&mut self, sfx: Sfx) {
+        let volume = self.master_volume * self.sfx_volume;
+        self.cues.push(Cue { sfx, volume, pan: 0.0 });
+    }
+
+    // quieter with distance, panned toward the side it came from
+    pub fn play_at(&mut self, sfx: Sfx, position: Vector2) {
+        let falloff = 1.0 - (position - self.listener).length() / HEARING_DISTANCE;
+        if falloff <= 0.0 {
+            return;
+        }
+        let volume = self.master_volume * self.sfx_volume * falloff;
+        let pan = ((position.x - self.listener.x) / (HEARING_DISTANCE / 2.0)).clamp(-1.0, 1.0);
+        self.cues.push(Cue { sfx, volume, pan });
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::HookFired { position } => self.play_at(Sfx::HookFire, position),
+            GameEvent::HookAttached { point, .. } => self.play_at(Sfx::HookHit, point),
+            GameEvent::HookReleased { position } => self.play_at(Sfx::RopeSnap, position),
+            GameEvent::Jumped { position } => self.play_at(Sfx::Jump, position),
+            GameEvent::Landed { position, speed } if speed > LANDING_SPEED => self.play_at(Sfx::Land, position),
+            // not positional, the camera hasn't caught up with the spawn point yet
+            GameEvent::Respawned { .. } => self.play(Sfx::Respawn),
+            GameEvent::Collected { position, .. } => self.play_at(Sfx::Pickup, position),
+            GameEvent::Caught { position } => self.play_at(Sfx::Caught, position),
+            _ => {}
+        }
+    }
+
+    // fades every track toward the mix for this height
+    pub fn update_music(&mut self, delta_time: f32, height: f32) {
+        let (from, to, t) = blend_at(height);
+        for (i, biome) in Biome::ALL.iter().enumerate() {
+            let mut target = 0.0;
+            if *biome == from {
+                target += 1.0 - t;
+            }
+            if *biome == to {
+                target += t;
+            }
+            let step = delta_time / MUSIC_FADE;
+            self.music[i] = if self.music[i] < target {
+                (self.music[i] + step).min(target)
+            } else {
+                (self.music[i] - step).max(target)
+            };
+        }
+    }
+
+    pub fn track_volume(&self, index: usize) -> f32 {
+        self.music[index] * self.master_volume * self.music_volume
+    }
+}
+
+struct Device<'a> {
+    sounds: Vec<Sound<'a>>, // in Sfx::ALL order
+    music: Vec<Sound<'a>>,  // in Biome::ALL order
+}
+
+// None for the device is the null backend, everything is mixed but nothing is played
+pub struct Audio<'a> {
+    pub mixer: Mixer,
+    device: Option<Device<'a>>,
+}
+
+impl<'a> Audio<'a> {
+    pub fn new(audio: Option<&'a RaylibAudio>, settings: &Settings) -> Self {
+        let device = audio.and_then(|audio| {
+            let sounds: Option<Vec<Sound>> =
+                Sfx::ALL.iter().map(|sfx| load_sound(audio, sfx.name(), || sfx.samples())).collect();
+            let music: Option<Vec<Sound>> = Biome::ALL
+                .iter()
+                .map(|biome| load_sound(audio, &format!("music_{}", biome.name().to_lowercase()), || music_samples(*biome)))
+                .collect();
+            Some(Device { sounds: sounds?, music: music? })
+        });
+        Self { mixer: Mixer::new(settings), device }
+    }
+
+    // once a frame, after everything that makes sounds
+    pub fn update(&mut self, delta_time: f32, settings: &Settings, listener: Vector2) {
+        self.mixer.set_volumes(settings);
+        self.mixer.listener = listener;
+        self.mixer.update_music(delta_time, height_of(listener));
+        let cues: Vec<Cue> = self.mixer.cues.drain(..).collect();
+        let Some(device) = self.device.as_ref() else {
+            return;
+        };
+        for cue in cues {
+            let sound = &device.sounds[cue.sfx as usize];
+            sound.set_volume(cue.volume);
+            // raylib's pan is 1 for fully left
+            sound.set_pan(0.5 - cue.pan / 2.0);
+            sound.play();
+        }
+        for (i, track) in device.music.iter().enumerate() {
+            let volume = self.mixer.track_volume(i);
+            track.set_volume(volume);
+            // sounds don't loop on their own
+            if volume > 0.0 && !track.is_playing() {
+                track.play();
+            } else if volume <= 0.0 && track.is_playing() {
+                track.stop();
+            }
+        }
+    }
+}
+
+pub fn open_device() -> Option<RaylibAudio> {
+    match RaylibAudio::init_audio_device() {
+        Ok(audio) => Some(audio),
+        Err(err) => {
+            eprintln!("no audio device, playing silently: {}", err);
+            None
+        }
+    }
+}
+
+// a wav file in AUDIO_DIR if there is one, otherwise the generated sound
+fn load_sound<'a>(audio: &'a RaylibAudio, name: &str, generate: impl FnOnce() -> Vec<f32>) -> Option<Sound<'a>> {
+    let path = Path::new(AUDIO_DIR).join(format!("{}.wav", name));
+    if path.exists() {
+        match audio.new_sound(&path.to_string_lossy()) {
+            Ok(sound) => return Some(sound),
+            Err(err) => eprintln!("failed to load {}: {}", path.display(), err),
+        }
+    }
+    let wave = match audio.new_wave_from_memory(".wav", &wav_bytes(&generate())) {
+        Ok(wave) => wave,
+        Err(err) => {
+            eprintln!("failed to build sound {}: {}", name, err);
+            return None;
+        }
+    };
+    audio.new_sound_from_wave(&wave).ok()
+}
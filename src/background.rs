@@ -1,47 +1,256 @@
 use raylib::prelude::*;
 
+use std::collections::HashMap;
+
+use darkswing::biome::{Biome, Theme, blend_at, mix_color, theme_at};
+use darkswing::object::Object;
+use darkswing::progress::height_of;
+use darkswing::seeded_random_range;
+use darkswing::sprites::{self, Atlas};
+
 pub const TILE_SIZE: f32 = 100.0;
 const HALF_WORLD_WIDTH: f32 = 800.0; // covers -800..800 (1600 width)
+const PROP_CHANCE: f32 = 0.08;
+
+// scroll factor, cell size and opacity, furthest first
+const PARALLAX_LAYERS: [(f32, f32, f32); 3] = [(0.2, 420.0, 0.25), (0.4, 300.0, 0.35), (0.6, 220.0, 0.45)];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TileVariant {
+    Plain,
+    Cracked,
+    Pipe,
+    Window,
+}
+
+// decoration hung on a tile now and then
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Prop {
+    Chain,
+    Lantern,
+    Vent,
+}
 
 #[derive(Copy, Clone)]
 pub struct Tile {
     pub position: Vector2, // top-left corner
+    pub variant: TileVariant,
+    pub prop: Option<Prop>,
 }
 
 impl Tile {
-    pub fn draw(&self, d: &mut impl RaylibDraw) {
+    // blend and theme come from the tile's row, see row_theme
+    pub fn draw(&self, d: &mut impl RaylibDraw, blend: (Biome, Biome, f32), theme: &Theme) {
+        let (from, to, t) = blend;
+
         // base tile
         d.draw_rectangle(
             self.position.x as i32,
             self.position.y as i32,
             TILE_SIZE as i32,
             TILE_SIZE as i32,
-            Color::DARKGRAY,
+            theme.tile,
         );
 
-        let inner_size = (TILE_SIZE * 0.3) as i32; // 30% of tile size
-        let offset = ((TILE_SIZE as i32) - inner_size) / 2;
-        d.draw_rectangle(
-            (self.position.x as i32) + offset,
-            (self.position.y as i32) + offset,
-            inner_size,
-            inner_size,
-            Color::new(128, 128, 128, 128), // gray with alpha
-        );
+        // patterns cross-fade between biomes
+        self.draw_pattern(d, from, theme.tile_accent.alpha(1.0 - t));
+        if to != from {
+            self.draw_pattern(d, to, theme.tile_accent.alpha(t));
+        }
+
+        let shade = mix_color(theme.tile, Color::BLACK, 0.5);
+        self.draw_variant(d, shade, theme.background);
+        if let Some(prop) = self.prop {
+            self.draw_prop(d, prop, shade);
+        }
+    }
+
+    fn draw_variant(&self, d: &mut impl RaylibDraw, shade: Color, background: Color) {
+        let p = self.position;
+        match self.variant {
+            TileVariant::Plain => {}
+            TileVariant::Cracked => {
+                let points = [(20.0, 10.0), (35.0, 30.0), (28.0, 45.0), (45.0, 60.0), (40.0, 80.0)];
+                for pair in points.windows(2) {
+                    let from = p + Vector2::new(pair[0].0, pair[0].1);
+                    let to = p + Vector2::new(pair[1].0, pair[1].1);
+                    d.draw_line_ex(from, to, 2.0, shade);
+                }
+                d.draw_line_ex(p + Vector2::new(28.0, 45.0), p + Vector2::new(12.0, 55.0), 2.0, shade);
+            }
+            TileVariant::Pipe => {
+                d.draw_rectangle_v(p + Vector2::new(0.0, 62.0), Vector2::new(TILE_SIZE, 12.0), shade);
+                // joints
+                for x in [20.0, 70.0] {
+                    d.draw_rectangle_v(p + Vector2::new(x, 59.0), Vector2::new(8.0, 18.0), shade);
+                }
+            }
+            TileVariant::Window => {
+                let rect = Rectangle::new(p.x + 25.0, p.y + 20.0, 50.0, 45.0);
+                d.draw_rectangle_rec(rect, background);
+                d.draw_rectangle_lines_ex(rect, 3.0, shade);
+                d.draw_line_ex(Vector2::new(rect.x + 25.0, rect.y), Vector2::new(rect.x + 25.0, rect.y + 45.0), 2.0, shade);
+            }
+        }
+    }
+
+    fn draw_prop(&self, d: &mut impl RaylibDraw, prop: Prop, shade: Color) {
+        let top = self.position + Vector2::new(TILE_SIZE / 2.0, 0.0);
+        match prop {
+            Prop::Chain => {
+                for link in 0..6 {
+                    d.draw_circle_lines(top.x as i32, (top.y + 6.0 + link as f32 * 9.0) as i32, 4.0, shade);
+                }
+            }
+            Prop::Lantern => {
+                d.draw_line_ex(top, top + Vector2::new(0.0, 30.0), 2.0, shade);
+                d.draw_rectangle_v(top + Vector2::new(-6.0, 30.0), Vector2::new(12.0, 16.0), Color::GOLD.alpha(0.8));
+            }
+            Prop::Vent => {
+                let center = self.position + Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+                d.draw_circle_v(center, 18.0, shade);
+                for slat in -1..=1 {
+                    let y = center.y + slat as f32 * 7.0;
+                    d.draw_line_ex(Vector2::new(center.x - 12.0, y), Vector2::new(center.x + 12.0, y), 2.0, Color::BLACK);
+                }
+            }
+        }
+    }
+
+    fn draw_pattern(&self, d: &mut impl RaylibDraw, biome: Biome, color: Color) {
+        let x = self.position.x as i32;
+        let y = self.position.y as i32;
+        let size = TILE_SIZE as i32;
+        match biome {
+            Biome::Cave => {
+                let inner_size = (TILE_SIZE * 0.3) as i32; // 30% of tile size
+                let offset = (size - inner_size) / 2;
+                d.draw_rectangle(x + offset, y + offset, inner_size, inner_size, color);
+            }
+            Biome::Industrial => {
+                // grate
+                for bar in 1..4 {
+                    d.draw_rectangle(x + 10, y + bar * size / 4 - 3, size - 20, 6, color);
+                }
+            }
+            Biome::Sky => {
+                // cloud puff
+                let center = self.position + Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+                d.draw_circle_v(center, TILE_SIZE * 0.18, color);
+                d.draw_circle_v(center + Vector2::new(-TILE_SIZE * 0.18, TILE_SIZE * 0.06), TILE_SIZE * 0.12, color);
+                d.draw_circle_v(center + Vector2::new(TILE_SIZE * 0.18, TILE_SIZE * 0.06), TILE_SIZE * 0.12, color);
+            }
+            Biome::Space => {
+                // a few stars, spread by position so neighbours differ
+                let hash = (x.wrapping_mul(73) ^ y.wrapping_mul(151)).unsigned_abs();
+                for star in 0..3u32 {
+                    let sx = (hash >> (star * 5)) % (size as u32 - 10) + 5;
+                    let sy = (hash >> (star * 5 + 3)) % (size as u32 - 10) + 5;
+                    d.draw_circle(x + sx as i32, y + sy as i32, 1.5, color);
+                }
+            }
+        }
     }
 }
 
-pub fn generate_tile_layer(_seed: u64, layer_y: i32) -> Vec<Tile> {
+pub fn generate_tile_layer(seed: u64, layer_y: i32) -> Vec<Tile> {
     let mut tiles = Vec::<Tile>::new();
+    let layer_seed = seed ^ (layer_y as u64).wrapping_mul(0x9E3779B97F4A7C15);
 
     let mut x = -HALF_WORLD_WIDTH;
+    let mut i: u64 = 0;
     while x <= HALF_WORLD_WIDTH {
-        tiles.push(Tile { position: Vector2::new(x as f32, layer_y as f32) });
-        x += TILE_SIZE as f32;
+        let tile_seed = layer_seed.wrapping_add(i.wrapping_mul(0x2545F4914F6CDD1D));
+        let roll = seeded_random_range(tile_seed, 0.0, 1.0);
+        let variant = match roll {
+            r if r < 0.6 => TileVariant::Plain,
+            r if r < 0.75 => TileVariant::Cracked,
+            r if r < 0.9 => TileVariant::Pipe,
+            _ => TileVariant::Window,
+        };
+        let prop_roll = seeded_random_range(tile_seed ^ 0xA5A5, 0.0, 1.0);
+        let prop = (prop_roll < PROP_CHANCE).then(|| {
+            [Prop::Chain, Prop::Lantern, Prop::Vent][(prop_roll / PROP_CHANCE * 3.0) as usize % 3]
+        });
+
+        tiles.push(Tile { position: Vector2::new(x, layer_y as f32), variant, prop });
+        x += TILE_SIZE;
+        i += 1;
     }
     tiles
 }
 
+// cheap stable noise for things drawn every frame, 0..1
+fn hash01(seed: u64, a: i64, b: i64) -> f32 {
+    let mut z = seed ^ (a as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ (b as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// distant silhouettes behind the play area, each layer lags the camera by its factor
+pub fn draw_parallax(d: &mut impl RaylibDraw, seed: u64, camera_target: Vector2, view_size: Vector2) {
+    for (index, (factor, cell, opacity)) in PARALLAX_LAYERS.iter().enumerate() {
+        // a layer point p is drawn at p + offset, so it moves at `factor` of the camera's speed
+        let offset = camera_target * (1.0 - factor);
+        let view_min = camera_target - view_size / 2.0 - offset;
+        let view_max = camera_target + view_size / 2.0 - offset;
+        let layer_seed = seed.wrapping_add(index as u64 * 7919);
+
+        let (first_x, last_x) = ((view_min.x / cell).floor() as i64 - 1, (view_max.x / cell).ceil() as i64);
+        let (first_y, last_y) = ((view_min.y / cell).floor() as i64 - 1, (view_max.y / cell).ceil() as i64);
+        for cx in first_x..=last_x {
+            for cy in first_y..=last_y {
+                if hash01(layer_seed, cx, cy) > 0.45 {
+                    continue;
+                }
+                let width = cell * (0.15 + 0.35 * hash01(layer_seed ^ 1, cx, cy));
+                let height = cell * (0.4 + 1.0 * hash01(layer_seed ^ 2, cx, cy));
+                let x = cx as f32 * cell + (cell - width) * hash01(layer_seed ^ 3, cx, cy);
+                let position = Vector2::new(x, cy as f32 * cell) + offset;
+
+                let theme = theme_at(height_of(position));
+                let color = mix_color(theme.tile, Color::BLACK, 0.6 - 0.15 * index as f32).alpha(*opacity);
+                d.draw_rectangle_v(position, Vector2::new(width, height), color);
+            }
+        }
+    }
+}
+
+// every tile in a row sits at the same height, so the biome blend is worked out once per row
+fn row_theme(layer_y: i32) -> ((Biome, Biome, f32), Theme) {
+    let (from, to, t) = blend_at(height_of(Vector2::new(0.0, layer_y as f32 + TILE_SIZE / 2.0)));
+    ((from, to, t), from.theme().mix(&to.theme(), t))
+}
+
+// sky, tiles, parallax and cubes, everything behind the players, drawn inside the camera
+// view_size is in screen pixels
+pub fn draw_world<'a>(
+    d: &mut impl RaylibDraw,
+    seed: u64,
+    camera: Camera2D,
+    view_size: Vector2,
+    tilemap: &HashMap<i32, Vec<Tile>>,
+    objects: impl Iterator<Item = &'a Object>,
+    atlas: Option<&Atlas>,
+) {
+    let theme = theme_at(height_of(camera.target));
+    d.clear_background(theme.background);
+    for (layer_y, tiles) in tilemap {
+        let (blend, row_theme) = row_theme(*layer_y);
+        for tile in tiles {
+            tile.draw(d, blend, &row_theme);
+        }
+    }
+    // the tiles are opaque and cover the view, so the translucent parallax goes over them
+    // a zoomed out camera sees more of the world
+    draw_parallax(d, seed, camera.target, view_size / camera.zoom);
+    for object in objects {
+        sprites::draw_object(d, atlas, object, &theme);
+    }
+}
+
 pub fn tile_layers_at_y(player_y: f32) -> Vec<i32> {
     const BUFFER: i32 = 1000;
 
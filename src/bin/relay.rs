@@ -0,0 +1,18 @@
+// relay for live ghost races, `cargo run --bin relay -- [host:port]`
+use darkswing::relay::{DEFAULT_RELAY_ADDR, Relay};
+
+fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_RELAY_ADDR.to_string());
+    let mut relay = match Relay::bind(&addr) {
+        Ok(relay) => relay,
+        Err(err) => {
+            eprintln!("could not bind {}: {}", addr, err);
+            std::process::exit(1);
+        }
+    };
+    println!("relay listening on {}", relay.local_addr().unwrap());
+    if let Err(err) = relay.serve() {
+        eprintln!("relay stopped: {}", err);
+        std::process::exit(1);
+    }
+}
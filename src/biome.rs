@@ -0,0 +1,165 @@
+use raylib::prelude::*;
+
+use crate::layergen::LayerProfile;
+use crate::object::ObjectKind;
+
+pub const TRANSITION: f32 = 400.0; // height over which one biome fades into the next
+
+// altitude bands, each starts at a height and runs until the next one
+const BANDS: [(Biome, f32); 4] = [
+    (Biome::Cave, f32::MIN),
+    (Biome::Industrial, 1500.0),
+    (Biome::Sky, 3500.0),
+    (Biome::Space, 6000.0),
+];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Biome {
+    Cave,
+    Industrial,
+    Sky,
+    Space,
+}
+
+// how a biome looks and what the generator puts in it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub background: Color,
+    pub tile: Color,
+    pub tile_accent: Color,
+    pub solid: Color,
+    pub unhookable: Color,
+    pub hazard: Color,
+    pub ambient: u8, // how much you see without a light, 0 is pitch black
+    pub profile: LayerProfile,
+    pub unhookable_chance: f32,
+    pub hazard_chance: f32,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 4] = [Biome::Cave, Biome::Industrial, Biome::Sky, Biome::Space];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Cave => "Cave",
+            Biome::Industrial => "Industrial",
+            Biome::Sky => "Sky",
+            Biome::Space => "Space",
+        }
+    }
+
+    pub fn at_height(height: f32) -> Biome {
+        BANDS.iter().rev().find(|(_, start)| height >= *start).map_or(Biome::Cave, |(biome, _)| *biome)
+    }
+
+    pub fn theme(&self) -> Theme {
+        match self {
+            // the original look, nothing dangerous at the start
+            Biome::Cave => Theme {
+                background: Color::RAYWHITE,
+                tile: Color::DARKGRAY,
+                tile_accent: Color::new(128, 128, 128, 128),
+                solid: Color::RED,
+                unhookable: Color::GRAY,
+                hazard: Color::PURPLE,
+                ambient: 0,
+                profile: LayerProfile::Normal,
+                unhookable_chance: 0.0,
+                hazard_chance: 0.0,
+            },
+            Biome::Industrial => Theme {
+                background: Color::new(60, 50, 40, 255),
+                tile: Color::new(70, 60, 50, 255),
+                tile_accent: Color::new(150, 110, 60, 160),
+                solid: Color::ORANGE,
+                unhookable: Color::new(90, 90, 100, 255),
+                hazard: Color::new(230, 200, 0, 255),
+                ambient: 25,
+                profile: LayerProfile::Dense,
+                unhookable_chance: 0.2,
+                hazard_chance: 0.08,
+            },
+            Biome::Sky => Theme {
+                background: Color::SKYBLUE,
+                tile: Color::new(110, 170, 220, 255),
+                tile_accent: Color::new(240, 245, 255, 140),
+                solid: Color::WHITE,
+                unhookable: Color::new(170, 190, 210, 255),
+                hazard: Color::new(120, 40, 160, 255),
+                ambient: 70,
+                profile: LayerProfile::Sparse,
+                unhookable_chance: 0.1,
+                hazard_chance: 0.1,
+            },
+            Biome::Space => Theme {
+                background: Color::new(5, 5, 20, 255),
+                tile: Color::new(10, 10, 30, 255),
+                tile_accent: Color::new(230, 230, 255, 200),
+                solid: Color::new(120, 200, 255, 255),
+                unhookable: Color::new(60, 70, 90, 255),
+                hazard: Color::MAGENTA,
+                ambient: 15,
+                profile: LayerProfile::Sparse,
+                unhookable_chance: 0.2,
+                hazard_chance: 0.15,
+            },
+        }
+    }
+}
+
+impl Theme {
+    pub fn object_color(&self, kind: ObjectKind) -> Color {
+        match kind {
+            ObjectKind::Solid => self.solid,
+            ObjectKind::Unhookable => self.unhookable,
+            ObjectKind::Hazard => self.hazard,
+        }
+    }
+
+    // colours fade, the generator settings switch halfway
+    pub fn mix(&self, other: &Theme, t: f32) -> Theme {
+        let nearer = if t < 0.5 { self } else { other };
+        Theme {
+            background: mix_color(self.background, other.background, t),
+            tile: mix_color(self.tile, other.tile, t),
+            tile_accent: mix_color(self.tile_accent, other.tile_accent, t),
+            solid: mix_color(self.solid, other.solid, t),
+            unhookable: mix_color(self.unhookable, other.unhookable, t),
+            hazard: mix_color(self.hazard, other.hazard, t),
+            ambient: mix_u8(self.ambient, other.ambient, t),
+            ..*nearer
+        }
+    }
+}
+
+fn mix_u8(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t).round() as u8
+}
+
+pub fn mix_color(a: Color, b: Color, t: f32) -> Color {
+    Color::new(mix_u8(a.r, b.r, t), mix_u8(a.g, b.g, t), mix_u8(a.b, b.b, t), mix_u8(a.a, b.a, t))
+}
+
+// the two biomes around a height and how far into the second one it is
+pub fn blend_at(height: f32) -> (Biome, Biome, f32) {
+    for pair in BANDS.windows(2) {
+        let (from, to, start) = (pair[0].0, pair[1].0, pair[1].1);
+        let t = (height - (start - TRANSITION / 2.0)) / TRANSITION;
+        if (0.0..1.0).contains(&t) {
+            return (from, to, t);
+        }
+    }
+    let biome = Biome::at_height(height);
+    (biome, biome, 0.0)
+}
+
+pub fn theme_at(height: f32) -> Theme {
+    let (from, to, t) = blend_at(height);
+    from.theme().mix(&to.theme(), t)
+}
+
+// the darkness mask is cleared to this instead of black
+pub fn ambient_color(height: f32) -> Color {
+    let ambient = theme_at(height).ambient;
+    Color::new(ambient, ambient, ambient, 255)
+}
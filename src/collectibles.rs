@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+
+use raylib::prelude::*;
+
+use crate::events::{EventQueue, GameEvent};
+use crate::object::{Object, ObjectKind};
+use crate::player::{PLAYER_SIZE, Player};
+use super::seeded_random_range;
+
+pub const PICKUP_RADIUS: f32 = 12.0;
+pub const ROPE_BONUS: f32 = 25.0; // extra rope from each upgrade, lasts until the run ends
+const HOVER: f32 = 20.0; // how far above its cube an item floats
+const GLOW_RADIUS: f32 = 40.0;
+
+// chance of each item on a cube, whatever is left over is nothing
+const ROPE_CHANCE: f32 = 0.03;
+const BATTERY_CHANCE: f32 = 0.1;
+const SHARD_CHANCE: f32 = 0.45;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CollectibleKind {
+    Shard,       // currency for the shop
+    Battery,     // refills the flashlight
+    RopeUpgrade, // longer rope for the rest of the run
+}
+
+impl CollectibleKind {
+    pub fn color(&self) -> Color {
+        match self {
+            CollectibleKind::Shard => Color::GOLD,
+            CollectibleKind::Battery => Color::LIME,
+            CollectibleKind::RopeUpgrade => Color::ORANGE,
+        }
+    }
+
+    // returns the shards it was worth, the caller decides where they go
+    pub fn apply(&self, player: &mut Player) -> u32 {
+        match self {
+            CollectibleKind::Shard => return 1,
+            CollectibleKind::Battery => player.battery = 1.0,
+            CollectibleKind::RopeUpgrade => player.grappler.rope_bonus += ROPE_BONUS,
+        }
+        0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collectible {
+    pub kind: CollectibleKind,
+    pub position: Vector2,
+    pub layer: i32,
+    pub index: usize, // which cube of the layer it sits on
+}
+
+impl Collectible {
+    pub fn draw(&self, d: &mut impl RaylibDraw, time: f32) {
+        // bob a little so they read as loose items, not part of the level
+        let position = self.position + Vector2::new(0.0, (time * 3.0 + self.index as f32).sin() * 3.0);
+        let color = self.kind.color();
+        match self.kind {
+            CollectibleKind::Shard => d.draw_poly(position, 4, 6.0, time * 90.0, color),
+            CollectibleKind::Battery => {
+                d.draw_rectangle_v(position - Vector2::new(4.0, 6.0), Vector2::new(8.0, 12.0), color);
+                d.draw_rectangle_v(position - Vector2::new(2.0, 8.0), Vector2::new(4.0, 2.0), color);
+            }
+            CollectibleKind::RopeUpgrade => {
+                d.draw_ring(position, 4.0, 7.0, 0.0, 360.0, 16, color);
+            }
+        }
+    }
+}
+
+// one roll per cube, hazards carry nothing, items float above the cube's top
+pub fn generate_collectibles(seed: u64, layer_index: i32, cubes: &[Object]) -> Vec<Collectible> {
+    let mut items = Vec::new();
+    for (i, cube) in cubes.iter().enumerate() {
+        if cube.kind == ObjectKind::Hazard {
+            continue;
+        }
+        let item_seed = seed ^ (layer_index as u64).wrapping_mul(0x2545F4914F6CDD1D).wrapping_add(i as u64 * 37);
+        let roll = seeded_random_range(item_seed, 0.0, 1.0);
+        let kind = if roll < ROPE_CHANCE {
+            CollectibleKind::RopeUpgrade
+        } else if roll < ROPE_CHANCE + BATTERY_CHANCE {
+            CollectibleKind::Battery
+        } else if roll < ROPE_CHANCE + BATTERY_CHANCE + SHARD_CHANCE {
+            CollectibleKind::Shard
+        } else {
+            continue;
+        };
+        let position = Vector2::new(cube.position.x + cube.size.x / 2.0, cube.position.y - HOVER);
+        items.push(Collectible { kind, position, layer: layer_index, index: i });
+    }
+    items
+}
+
+// items in the loaded layers, and which ones this run already took so they don't come back
+pub struct Collectibles {
+    pub layers: HashMap<i32, Vec<Collectible>>,
+    picked: HashSet<(i32, usize)>,
+}
+
+impl Collectibles {
+    pub fn new() -> Self {
+        Self { layers: HashMap::new(), picked: HashSet::new() }
+    }
+
+    pub fn load_layer(&mut self, seed: u64, layer_index: i32, cubes: &[Object]) {
+        let mut items = generate_collectibles(seed, layer_index, cubes);
+        items.retain(|item| !self.picked.contains(&(item.layer, item.index)));
+        self.layers.insert(layer_index, items);
+    }
+
+    pub fn unload_layer(&mut self, layer_index: i32) {
+        self.layers.remove(&layer_index);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Collectible> {
+        self.layers.values().flatten()
+    }
+
+    // anything the player touches is taken and reported as an event
+    pub fn pick_up(&mut self, position: Vector2, events: &mut EventQueue) -> Vec<CollectibleKind> {
+        let mut taken = Vec::new();
+        for items in self.layers.values_mut() {
+            items.retain(|item| {
+                if item.position.distance_to(position) > PICKUP_RADIUS + PLAYER_SIZE {
+                    return true;
+                }
+                self.picked.insert((item.layer, item.index));
+                events.push(GameEvent::Collected { position: item.position, kind: item.kind });
+                taken.push(item.kind);
+                false
+            });
+        }
+        taken
+    }
+
+    // world positions, a faint glow so they can be spotted in the dark
+    pub fn lights(&self) -> impl Iterator<Item = (Vector2, f32)> + '_ {
+        self.iter().map(|item| (item.position, GLOW_RADIUS))
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, time: f32) {
+        for item in self.iter() {
+            item.draw(d, time);
+        }
+    }
+}
+
+impl Default for Collectibles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
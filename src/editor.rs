@@ -0,0 +1,485 @@
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::biome::theme_at;
+use crate::level::{DEFAULT_LIGHT_RADIUS, Level, LevelLight, LevelObject, draw_checkpoints};
+use crate::object::ObjectKind;
+use crate::player::{FALL_Y, PLAYER_SIZE};
+use crate::progress::{CHECKPOINT_RADIUS, height_of};
+
+const GRID: f32 = 10.0;
+const MAJOR_GRID: f32 = 100.0;
+const HANDLE_SIZE: f32 = 8.0; // resize handle at the bottom right corner
+const PAN_SPEED: f32 = 600.0;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+const MESSAGE_TIME: f32 = 2.5;
+const FONT_SIZE: i32 = 20;
+
+#[derive(PartialEq, Clone, Copy)]
+enum Tool {
+    Object,
+    Spawn,
+    Checkpoint,
+    Light,
+}
+
+impl Tool {
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Object => "Objects",
+            Tool::Spawn => "Spawn",
+            Tool::Checkpoint => "Checkpoints",
+            Tool::Light => "Lights",
+        }
+    }
+}
+
+enum Drag {
+    Create(Vector2),
+    Move { index: usize, offset: Vector2 },
+    Resize(usize),
+}
+
+pub enum EditorEvent {
+    Play,
+    Quit,
+}
+
+fn snap(point: Vector2) -> Vector2 {
+    Vector2::new((point.x / GRID).round() * GRID, (point.y / GRID).round() * GRID)
+}
+
+fn rect_of(object: &LevelObject) -> Rectangle {
+    Rectangle::new(object.position[0], object.position[1], object.size[0], object.size[1])
+}
+
+fn contains(rect: Rectangle, point: Vector2) -> bool {
+    point.x >= rect.x && point.x <= rect.x + rect.width && point.y >= rect.y && point.y <= rect.y + rect.height
+}
+
+pub struct Editor {
+    pub level: Level,
+    pub path: String,
+    camera: Camera2D,
+    tool: Tool,
+    kind: ObjectKind, // kind of newly placed objects
+    selected: Option<usize>,
+    drag: Option<Drag>,
+    mouse: Vector2, // in world space
+    dirty: bool,
+    confirm_quit: bool,
+    message: Option<(String, f32)>,
+}
+
+impl Editor {
+    // opens the file if it exists, otherwise starts a new tower that saves there
+    pub fn new(path: &str) -> Self {
+        let (level, message) = if Path::new(path).exists() {
+            match Level::load(path) {
+                Ok(level) => (level, format!("Loaded {}", path)),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    (Level::new(), format!("{} (saving will overwrite it)", err))
+                }
+            }
+        } else {
+            (Level::new(), format!("New level, saves to {}", path))
+        };
+
+        Self {
+            camera: Camera2D { target: level.spawn(), zoom: 1.0, ..Default::default() },
+            level,
+            path: path.to_string(),
+            tool: Tool::Object,
+            kind: ObjectKind::Solid,
+            selected: None,
+            drag: None,
+            mouse: Vector2::zero(),
+            dirty: false,
+            confirm_quit: false,
+            message: Some((message, MESSAGE_TIME)),
+        }
+    }
+
+    fn show(&mut self, message: String) {
+        self.message = Some((message, MESSAGE_TIME));
+    }
+
+    fn changed(&mut self) {
+        self.dirty = true;
+        self.confirm_quit = false;
+    }
+
+    fn save(&mut self) -> bool {
+        match self.level.save(&self.path) {
+            Ok(()) => {
+                self.dirty = false;
+                self.show(format!("Saved {}", self.path));
+                true
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                self.show(err);
+                false
+            }
+        }
+    }
+
+    // topmost object under the point, later objects draw on top
+    fn object_at(&self, point: Vector2) -> Option<usize> {
+        self.level.objects.iter().rposition(|object| contains(rect_of(object), point))
+    }
+
+    fn handle_at(&self, point: Vector2) -> Option<usize> {
+        let index = self.selected?;
+        let rect = rect_of(&self.level.objects[index]);
+        let corner = Vector2::new(rect.x + rect.width, rect.y + rect.height);
+        (point.distance_to(corner) <= HANDLE_SIZE / self.camera.zoom).then_some(index)
+    }
+
+    fn checkpoint_at(&self, point: Vector2) -> Option<usize> {
+        self.level.checkpoints().iter().position(|checkpoint| point.distance_to(*checkpoint) <= CHECKPOINT_RADIUS)
+    }
+
+    fn light_at(&self, point: Vector2) -> Option<usize> {
+        self.level.lights.iter().position(|light| {
+            point.distance_to(Vector2::new(light.position[0], light.position[1])) <= CHECKPOINT_RADIUS
+        })
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle) -> Option<EditorEvent> {
+        let delta_time = rl.get_frame_time();
+        if let Some((_, time)) = self.message.as_mut() {
+            *time -= delta_time;
+            if *time <= 0.0 {
+                self.message = None;
+            }
+        }
+
+        let screen = Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        self.camera.offset = screen / 2.0;
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            if self.dirty && !self.confirm_quit {
+                self.confirm_quit = true;
+                self.show("Unsaved changes, ESC again to quit".to_string());
+                return None;
+            }
+            return Some(EditorEvent::Quit);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F5) && self.save() {
+            return Some(EditorEvent::Play);
+        }
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_S) {
+            self.save();
+        }
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_L) {
+            match Level::load(&self.path) {
+                Ok(level) => {
+                    self.level = level;
+                    self.selected = None;
+                    self.drag = None;
+                    self.dirty = false;
+                    self.show(format!("Loaded {}", self.path));
+                }
+                Err(err) => self.show(err),
+            }
+        }
+
+        // camera, wasd would fight ctrl+s
+        if !ctrl {
+            let mut pan = Vector2::zero();
+            if rl.is_key_down(KeyboardKey::KEY_A) || rl.is_key_down(KeyboardKey::KEY_LEFT) {
+                pan.x -= 1.0;
+            }
+            if rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+                pan.x += 1.0;
+            }
+            if rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP) {
+                pan.y -= 1.0;
+            }
+            if rl.is_key_down(KeyboardKey::KEY_S) || rl.is_key_down(KeyboardKey::KEY_DOWN) {
+                pan.y += 1.0;
+            }
+            self.camera.target += pan * PAN_SPEED * delta_time / self.camera.zoom;
+        }
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.camera.zoom = (self.camera.zoom * (1.0 + wheel * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE) {
+            self.camera.target -= rl.get_mouse_delta() / self.camera.zoom;
+        }
+
+        // tools and settings
+        if rl.is_key_pressed(KeyboardKey::KEY_ONE) {
+            self.tool = Tool::Object;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_TWO) {
+            self.tool = Tool::Spawn;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_THREE) {
+            self.tool = Tool::Checkpoint;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_FOUR) {
+            self.tool = Tool::Light;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_K) {
+            // retypes the selection, and new objects follow
+            self.kind = match self.selected {
+                Some(index) => self.level.objects[index].kind.next(),
+                None => self.kind.next(),
+            };
+            if let Some(index) = self.selected {
+                self.level.objects[index].kind = self.kind;
+                self.changed();
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            // toggles endless generated layers above the tower, other sections are edited in the file
+            if self.level.procedural.is_empty() {
+                self.level.procedural.push(self.level.section_above());
+            } else {
+                self.level.procedural.clear();
+            }
+            self.changed();
+        }
+        if (rl.is_key_pressed(KeyboardKey::KEY_DELETE) || rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE))
+            && let Some(index) = self.selected.take()
+        {
+            self.level.objects.remove(index);
+            // a drag still in progress would point at the wrong object
+            self.drag = None;
+            self.changed();
+        }
+
+        let mouse = rl.get_screen_to_world2D(rl.get_mouse_position(), self.camera);
+        self.mouse = mouse;
+        let pressed = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+        let released = rl.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT);
+        let right_pressed = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+
+        match self.tool {
+            Tool::Object => self.update_objects(mouse, pressed, released, right_pressed),
+            Tool::Spawn => {
+                if pressed {
+                    let spawn = snap(mouse);
+                    self.level.spawn = [spawn.x, spawn.y];
+                    self.changed();
+                }
+            }
+            Tool::Checkpoint => {
+                if pressed && self.checkpoint_at(mouse).is_none() {
+                    let checkpoint = snap(mouse);
+                    self.level.checkpoints.push([checkpoint.x, checkpoint.y]);
+                    self.changed();
+                }
+                if right_pressed && let Some(index) = self.checkpoint_at(mouse) {
+                    self.level.checkpoints.remove(index);
+                    self.changed();
+                }
+            }
+            Tool::Light => {
+                if pressed && self.light_at(mouse).is_none() {
+                    let position = snap(mouse);
+                    self.level.lights.push(LevelLight { position: [position.x, position.y], radius: DEFAULT_LIGHT_RADIUS });
+                    self.changed();
+                }
+                if right_pressed && let Some(index) = self.light_at(mouse) {
+                    self.level.lights.remove(index);
+                    self.changed();
+                }
+            }
+        }
+        None
+    }
+
+    fn update_objects(&mut self, mouse: Vector2, pressed: bool, released: bool, right_pressed: bool) {
+        if right_pressed && let Some(index) = self.object_at(mouse) {
+            self.level.objects.remove(index);
+            self.selected = None;
+            self.drag = None;
+            self.changed();
+            return;
+        }
+
+        if pressed {
+            self.drag = if let Some(index) = self.handle_at(mouse) {
+                Some(Drag::Resize(index))
+            } else if let Some(index) = self.object_at(mouse) {
+                self.selected = Some(index);
+                self.kind = self.level.objects[index].kind;
+                let position = self.level.objects[index].position;
+                Some(Drag::Move { index, offset: Vector2::new(position[0], position[1]) - mouse })
+            } else {
+                self.selected = None;
+                Some(Drag::Create(snap(mouse)))
+            };
+        }
+
+        match self.drag {
+            Some(Drag::Move { index, offset }) => {
+                let position = snap(mouse + offset);
+                if self.level.objects[index].position != [position.x, position.y] {
+                    self.level.objects[index].position = [position.x, position.y];
+                    self.changed();
+                }
+            }
+            Some(Drag::Resize(index)) => {
+                let object = &mut self.level.objects[index];
+                let corner = snap(mouse);
+                let size = [(corner.x - object.position[0]).max(GRID), (corner.y - object.position[1]).max(GRID)];
+                if object.size != size {
+                    object.size = size;
+                    self.changed();
+                }
+            }
+            Some(Drag::Create(start)) if released => {
+                let rect = self.create_rect(start, mouse);
+                if rect.width >= GRID && rect.height >= GRID {
+                    self.level.objects.push(LevelObject {
+                        position: [rect.x, rect.y],
+                        size: [rect.width, rect.height],
+                        kind: self.kind,
+                    });
+                    self.selected = Some(self.level.objects.len() - 1);
+                    self.changed();
+                }
+            }
+            _ => {}
+        }
+        if released {
+            self.drag = None;
+        }
+    }
+
+    // dragging works in any direction
+    fn create_rect(&self, start: Vector2, mouse: Vector2) -> Rectangle {
+        let end = snap(mouse);
+        Rectangle::new(start.x.min(end.x), start.y.min(end.y), (end.x - start.x).abs(), (end.y - start.y).abs())
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, screen_size: Vector2) {
+        d.clear_background(Color::RAYWHITE);
+
+        {
+            let mut dcam = d.begin_mode2D(self.camera);
+            self.draw_grid(&mut dcam, screen_size);
+
+            // below this line you've fallen
+            let left = self.camera.target.x - screen_size.x / self.camera.zoom;
+            let right = self.camera.target.x + screen_size.x / self.camera.zoom;
+            dcam.draw_line_ex(Vector2::new(left, FALL_Y), Vector2::new(right, FALL_Y), 2.0, Color::BLACK);
+            for section in &self.level.procedural {
+                let bottom = section.bottom;
+                dcam.draw_line_ex(Vector2::new(left, bottom), Vector2::new(right, bottom), 2.0, Color::SKYBLUE);
+                let label = format!("{:?} layers above", section.profile);
+                dcam.draw_text(&label, left as i32 + 10, bottom as i32 - 20, 16, Color::SKYBLUE);
+                if let Some(top) = section.top {
+                    dcam.draw_line_ex(Vector2::new(left, top), Vector2::new(right, top), 2.0, Color::SKYBLUE);
+                }
+            }
+
+            let theme = theme_at(height_of(self.camera.target));
+            for object in &self.level.objects {
+                object.to_object().draw(&mut dcam, &theme);
+            }
+            if let Some(index) = self.selected {
+                let rect = rect_of(&self.level.objects[index]);
+                dcam.draw_rectangle_lines_ex(rect, 2.0 / self.camera.zoom, Color::YELLOW);
+                let handle = HANDLE_SIZE / self.camera.zoom;
+                dcam.draw_rectangle_rec(
+                    Rectangle::new(rect.x + rect.width - handle / 2.0, rect.y + rect.height - handle / 2.0, handle, handle),
+                    Color::YELLOW,
+                );
+            }
+            if let Some(Drag::Create(start)) = self.drag {
+                let rect = self.create_rect(start, self.mouse);
+                let color = theme.object_color(self.kind);
+                dcam.draw_rectangle_rec(rect, color.alpha(0.5));
+            }
+
+            for light in &self.level.lights {
+                let position = Vector2::new(light.position[0], light.position[1]);
+                dcam.draw_circle_v(position, light.radius, Color::GOLD.alpha(0.15));
+                dcam.draw_circle_v(position, 6.0, Color::GOLD);
+            }
+            draw_checkpoints(&mut dcam, &self.level.checkpoints(), None);
+            let spawn = self.level.spawn();
+            dcam.draw_circle_v(spawn, PLAYER_SIZE, Color::BLUE);
+            dcam.draw_text("SPAWN", spawn.x as i32 - 20, spawn.y as i32 - 30, 12, Color::BLUE);
+        }
+
+        self.draw_panel(d, screen_size);
+    }
+
+    fn draw_grid(&self, d: &mut impl RaylibDraw, screen_size: Vector2) {
+        let half = screen_size / 2.0 / self.camera.zoom;
+        let (left, right) = (self.camera.target.x - half.x, self.camera.target.x + half.x);
+        let (top, bottom) = (self.camera.target.y - half.y, self.camera.target.y + half.y);
+        // minor lines only when zoomed in enough to see them
+        let step = if self.camera.zoom >= 1.0 { GRID } else { MAJOR_GRID };
+
+        let mut x = (left / step).floor() * step;
+        while x <= right {
+            let major = (x / MAJOR_GRID).round() * MAJOR_GRID == x;
+            let color = if major { Color::LIGHTGRAY } else { Color::LIGHTGRAY.alpha(0.3) };
+            d.draw_line_v(Vector2::new(x, top), Vector2::new(x, bottom), color);
+            x += step;
+        }
+        let mut y = (top / step).floor() * step;
+        while y <= bottom {
+            let major = (y / MAJOR_GRID).round() * MAJOR_GRID == y;
+            let color = if major { Color::LIGHTGRAY } else { Color::LIGHTGRAY.alpha(0.3) };
+            d.draw_line_v(Vector2::new(left, y), Vector2::new(right, y), color);
+            y += step;
+        }
+    }
+
+    fn draw_panel(&self, d: &mut impl RaylibDraw, screen_size: Vector2) {
+        d.draw_rectangle(0, 0, screen_size.x as i32, 60, Color::new(0, 0, 0, 180));
+        let title = format!("{}{}", self.path, if self.dirty { " *" } else { "" });
+        d.draw_text(&title, 10, 8, FONT_SIZE, Color::WHITE);
+        let status = format!(
+            "Tool: {}   Kind: {}   Objects: {}   Checkpoints: {}   Lights: {}   Procedural sections: {}",
+            self.tool.name(),
+            self.kind.name(),
+            self.level.objects.len(),
+            self.level.checkpoints.len(),
+            self.level.lights.len(),
+            self.level.procedural.len(),
+        );
+        d.draw_text(&status, 10, 34, 16, Color::LIGHTGRAY);
+
+        if let Some((message, _)) = &self.message {
+            d.draw_text(message, 10, 70, FONT_SIZE, Color::DARKGRAY);
+        }
+        d.draw_text(
+            "1 objects  2 spawn  3 checkpoints  4 lights  drag place/move/resize  RMB delete  K kind  G procedural  CTRL+S save  CTRL+L load  F5 play  ESC quit",
+            10,
+            screen_size.y as i32 - 24,
+            14,
+            Color::DARKGRAY,
+        );
+    }
+}
+
+// runs the editor until you quit, or returns the saved level to play
+pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) -> Option<Level> {
+    let mut editor = Editor::new(path);
+    while !rl.window_should_close() {
+        match editor.update(rl) {
+            Some(EditorEvent::Play) => return Some(editor.level),
+            Some(EditorEvent::Quit) => return None,
+            None => {}
+        }
+
+        let screen_size = Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        let mut d = rl.begin_drawing(thread);
+        editor.draw(&mut d, screen_size);
+    }
+    None
+}
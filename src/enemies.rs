@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+
+use crate::layergen::layer_number;
+use crate::light::Visibility;
+use crate::object::{Object, ObjectKind};
+use crate::player::PLAYER_SIZE;
+use super::seeded_random_range;
+
+pub const ENEMY_SIZE: f32 = 8.0;
+pub const HUNT_RANGE: f32 = 350.0; // how close the player has to be before anything gives chase
+const CRAWL_SPEED: f32 = 40.0;
+const FLY_SPEED: f32 = 90.0;
+const SHADE_SPEED: f32 = 60.0;
+const FLEE_SPEED: f32 = 160.0;
+const HOVER: f32 = 80.0; // flyers and shades start this far above their cube
+
+// the first layers around the spawn are left alone, after that more cubes get something
+const SAFE_LAYERS: i32 = 3;
+const BASE_CHANCE: f32 = 0.1;
+const CHANCE_PER_LAYER: f32 = 0.01;
+const MAX_CHANCE: f32 = 0.4;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum EnemyKind {
+    Crawler, // walks the top of its cube while nobody is looking
+    Flyer,   // homes in on the player from the dark
+    Shade,   // creeps closer in the dark, runs from the flashlight
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Enemy {
+    pub kind: EnemyKind,
+    pub position: Vector2,
+    pub home: Vector2,
+    pub patrol: (f32, f32), // x range a crawler can walk
+    pub direction: f32,     // crawler heading, -1 left, 1 right
+}
+
+impl Enemy {
+    pub fn update(&mut self, delta_time: f32, target: Vector2, visibility: &Visibility) {
+        let lit = visibility.is_lit(self.position);
+        let in_range = self.position.distance_to(target) < HUNT_RANGE;
+        match self.kind {
+            // freezes in the light
+            EnemyKind::Crawler if !lit => {
+                self.position.x += self.direction * CRAWL_SPEED * delta_time;
+                if self.position.x <= self.patrol.0 || self.position.x >= self.patrol.1 {
+                    self.position.x = self.position.x.clamp(self.patrol.0, self.patrol.1);
+                    self.direction = -self.direction;
+                }
+            }
+            EnemyKind::Crawler => {}
+            EnemyKind::Flyer if !lit && in_range => self.move_toward(target, FLY_SPEED, delta_time),
+            EnemyKind::Flyer => self.move_toward(self.home, FLY_SPEED / 2.0, delta_time),
+            EnemyKind::Shade if visibility.in_beam(self.position) => {
+                let away = self.position - visibility.origin;
+                self.move_toward(self.position + away, FLEE_SPEED, delta_time);
+            }
+            EnemyKind::Shade if !lit && in_range => self.move_toward(target, SHADE_SPEED, delta_time),
+            EnemyKind::Shade => {}
+        }
+    }
+
+    fn move_toward(&mut self, target: Vector2, speed: f32, delta_time: f32) {
+        let offset = target - self.position;
+        let step = speed * delta_time;
+        if offset.length() <= step {
+            self.position = target;
+        } else {
+            self.position += offset.normalized() * step;
+        }
+    }
+
+    pub fn touches(&self, point: Vector2) -> bool {
+        self.position.distance_to(point) < ENEMY_SIZE + PLAYER_SIZE
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, time: f32) {
+        let position = self.position;
+        match self.kind {
+            EnemyKind::Crawler => {
+                // legs tick along while it walks
+                let step = (time * 12.0 + position.x).sin() * 2.0;
+                for side in [-1.0, 1.0] {
+                    let foot = position + Vector2::new(side * (ENEMY_SIZE + step), ENEMY_SIZE);
+                    d.draw_line_v(position, foot, Color::new(60, 20, 20, 255));
+                }
+                d.draw_ellipse(position.x as i32, position.y as i32, ENEMY_SIZE, ENEMY_SIZE * 0.7, Color::new(90, 25, 25, 255));
+            }
+            EnemyKind::Flyer => {
+                let flap = (time * 10.0).sin() * ENEMY_SIZE * 0.6;
+                for side in [-1.0, 1.0] {
+                    let tip = position + Vector2::new(side * ENEMY_SIZE * 2.0, -flap);
+                    let root = position + Vector2::new(side * ENEMY_SIZE * 0.5, 0.0);
+                    d.draw_line_ex(root, tip, 3.0, Color::new(60, 30, 90, 255));
+                }
+                d.draw_circle_v(position, ENEMY_SIZE * 0.8, Color::new(80, 40, 110, 255));
+            }
+            EnemyKind::Shade => {
+                d.draw_circle_v(position, ENEMY_SIZE * 1.2, Color::new(20, 20, 30, 180));
+                d.draw_circle_v(position, ENEMY_SIZE * 0.8, Color::new(10, 10, 15, 230));
+            }
+        }
+    }
+
+    // eyes show through the darkness so something is out there
+    pub fn draw_eyes(&self, d: &mut impl RaylibDraw) {
+        let color = match self.kind {
+            EnemyKind::Crawler => Color::ORANGE,
+            EnemyKind::Flyer => Color::RED,
+            EnemyKind::Shade => Color::SKYBLUE,
+        };
+        for side in [-1.0, 1.0] {
+            d.draw_circle_v(self.position + Vector2::new(side * 3.0, -2.0), 1.5, color);
+        }
+    }
+}
+
+// one roll per cube, the kind comes from where inside the chance the roll landed
+pub fn generate_enemies(seed: u64, layer_index: i32, cubes: &[Object]) -> Vec<Enemy> {
+    let layer = layer_number(layer_index as f32);
+    if layer < SAFE_LAYERS {
+        return Vec::new();
+    }
+    let chance = (BASE_CHANCE + CHANCE_PER_LAYER * layer as f32).min(MAX_CHANCE);
+
+    let mut enemies = Vec::new();
+    for (i, cube) in cubes.iter().enumerate() {
+        if cube.kind == ObjectKind::Hazard {
+            continue;
+        }
+        let enemy_seed = seed ^ (layer_index as u64).wrapping_mul(0xD6E8FEB86659FD93).wrapping_add(i as u64 * 41);
+        let roll = seeded_random_range(enemy_seed, 0.0, 1.0);
+        if roll >= chance {
+            continue;
+        }
+        let kind = match (roll / chance * 3.0) as usize {
+            0 => EnemyKind::Crawler,
+            1 => EnemyKind::Flyer,
+            _ => EnemyKind::Shade,
+        };
+
+        let top = Vector2::new(cube.position.x + cube.size.x / 2.0, cube.position.y - ENEMY_SIZE);
+        let position = match kind {
+            EnemyKind::Crawler => top,
+            _ => top - Vector2::new(0.0, HOVER),
+        };
+        let patrol = (cube.position.x + ENEMY_SIZE, cube.position.x + cube.size.x - ENEMY_SIZE);
+        enemies.push(Enemy { kind, position, home: position, patrol, direction: 1.0 });
+    }
+    enemies
+}
+
+// enemies in the loaded layers, they start over from home when a layer comes back
+pub struct Enemies {
+    pub layers: HashMap<i32, Vec<Enemy>>,
+}
+
+impl Enemies {
+    pub fn new() -> Self {
+        Self { layers: HashMap::new() }
+    }
+
+    pub fn load_layer(&mut self, seed: u64, layer_index: i32, cubes: &[Object]) {
+        self.layers.insert(layer_index, generate_enemies(seed, layer_index, cubes));
+    }
+
+    pub fn unload_layer(&mut self, layer_index: i32) {
+        self.layers.remove(&layer_index);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Enemy> {
+        self.layers.values().flatten()
+    }
+
+    // returns whether anything caught the target
+    pub fn update(&mut self, delta_time: f32, target: Vector2, visibility: &Visibility) -> bool {
+        let mut caught = false;
+        for enemy in self.layers.values_mut().flatten() {
+            enemy.update(delta_time, target, visibility);
+            caught |= enemy.touches(target);
+        }
+        caught
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, time: f32) {
+        for enemy in self.iter() {
+            enemy.draw(d, time);
+        }
+    }
+
+    pub fn draw_eyes(&self, d: &mut impl RaylibDraw) {
+        for enemy in self.iter() {
+            enemy.draw_eyes(d);
+        }
+    }
+}
+
+impl Default for Enemies {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,51 @@
+use raylib::prelude::*;
+
+use crate::collectibles::CollectibleKind;
+use crate::player::WallSide;
+
+// something that happened during a tick, positions are in world space
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    HookFired { position: Vector2 },
+    HookAttached { point: Vector2, origin: Vector2 }, // origin is where the rope comes from
+    HookMissed { position: Vector2 },
+    HookReleased { position: Vector2 },
+    Jumped { position: Vector2 },
+    Landed { position: Vector2, speed: f32 },
+    WallContact { position: Vector2, side: WallSide },
+    Fell { position: Vector2 },
+    Caught { position: Vector2 }, // something in the dark got the player
+    Respawned { position: Vector2 },
+    NewBestHeight { height: f32 },
+    CheckpointReached { position: Vector2 },
+    Collected { position: Vector2, kind: CollectibleKind },
+}
+
+// filled while a frame runs, read by every system that cares, cleared before the next frame
+pub struct EventQueue {
+    events: Vec<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GameEvent> {
+        self.events.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
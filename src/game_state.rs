@@ -0,0 +1,232 @@
+use raylib::prelude::*;
+
+use crate::achievements::Achievement;
+use crate::input::{MenuInput, menu_input};
+use crate::modes::GameMode;
+use crate::save::{SAVE_PATH, SaveData};
+use crate::shop::{self, Purchase, ShopItem};
+use crate::upgrades::{Upgrade, node};
+
+const LINE_HEIGHT: i32 = 50;
+const FONT_SIZE: i32 = 20;
+const SHOP_LINE_HEIGHT: i32 = 32;
+
+// menu rows after the modes
+const ACHIEVEMENTS_ROW: usize = GameMode::ALL.len();
+const SHOP_ROW: usize = GameMode::ALL.len() + 1;
+
+// what the main loop is doing, the world only simulates while Playing
+pub enum GameState {
+    ModeSelect { selected: usize },
+    Playing,
+    Finished { mode: GameMode, score: f32, new_best: bool },
+    Achievements,
+    Shop { selected: usize },
+}
+
+impl GameState {
+    pub fn is_playing(&self) -> bool {
+        matches!(self, GameState::Playing)
+    }
+
+    // menu input on the screens around a run, returns a mode to start
+    pub fn update(&mut self, rl: &RaylibHandle, save_data: &mut SaveData) -> Option<GameMode> {
+        let input = menu_input(rl);
+        match self {
+            GameState::Playing => None,
+            GameState::ModeSelect { selected } => {
+                // achievements and the shop sit below the modes
+                let count = GameMode::ALL.len() + 2;
+                match input {
+                    Some(MenuInput::Down) => *selected = (*selected + 1) % count,
+                    Some(MenuInput::Up) => *selected = (*selected + count - 1) % count,
+                    Some(MenuInput::Confirm) if *selected == ACHIEVEMENTS_ROW => *self = GameState::Achievements,
+                    Some(MenuInput::Confirm) if *selected == SHOP_ROW => *self = GameState::Shop { selected: 0 },
+                    Some(MenuInput::Confirm) => return Some(GameMode::ALL[*selected]),
+                    _ => {}
+                }
+                None
+            }
+            GameState::Achievements => {
+                if matches!(input, Some(MenuInput::Back | MenuInput::Confirm)) {
+                    *self = GameState::ModeSelect { selected: ACHIEVEMENTS_ROW };
+                }
+                None
+            }
+            GameState::Shop { selected } => {
+                let items = ShopItem::all();
+                match input {
+                    Some(MenuInput::Down) => *selected = (*selected + 1) % items.len(),
+                    Some(MenuInput::Up) => *selected = (*selected + items.len() - 1) % items.len(),
+                    Some(MenuInput::Confirm) => {
+                        let purchase = shop::buy(save_data, items[*selected]);
+                        if matches!(purchase, Purchase::Bought | Purchase::Equipped) {
+                            save_data.save(SAVE_PATH);
+                        }
+                    }
+                    Some(MenuInput::Back) => *self = GameState::ModeSelect { selected: SHOP_ROW },
+                    _ => {}
+                }
+                None
+            }
+            GameState::Finished { mode, .. } => {
+                let mode = *mode;
+                match input {
+                    Some(MenuInput::Confirm) => Some(mode),
+                    Some(MenuInput::Back) => {
+                        let selected = GameMode::ALL.iter().position(|m| *m == mode).unwrap_or(0);
+                        *self = GameState::ModeSelect { selected };
+                        None
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, save_data: &SaveData, screen_size: Vector2) {
+        match self {
+            GameState::Playing => {}
+            GameState::ModeSelect { selected } => draw_mode_select(d, *selected, save_data, screen_size),
+            GameState::Finished { mode, score, new_best } => {
+                draw_results(d, *mode, *score, *new_best, save_data, screen_size)
+            }
+            GameState::Achievements => draw_achievements(d, save_data, screen_size),
+            GameState::Shop { selected } => draw_shop(d, *selected, save_data, screen_size),
+        }
+    }
+}
+
+fn draw_mode_select(d: &mut impl RaylibDraw, selected: usize, save_data: &SaveData, screen_size: Vector2) {
+    d.draw_rectangle(0, 0, screen_size.x as i32, screen_size.y as i32, Color::new(0, 0, 0, 200));
+    d.draw_text("DARKSWING", 40, 30, 40, Color::WHITE);
+
+    for (i, mode) in GameMode::ALL.iter().enumerate() {
+        let color = if i == selected { Color::YELLOW } else { Color::LIGHTGRAY };
+        let y = 100 + i as i32 * LINE_HEIGHT;
+        if i == selected {
+            d.draw_text(">", 40, y, FONT_SIZE, color);
+        }
+
+        let best = save_data.high_score(*mode).map_or("-".to_string(), |score| mode.format_score(score));
+        d.draw_text(&format!("{} (best {})", mode.name(), best), 60, y, FONT_SIZE, color);
+        d.draw_text(&mode.description(), 60, y + 22, 16, Color::GRAY);
+    }
+
+    let earned = save_data.achievements.unlocked.len();
+    let extras = [
+        (ACHIEVEMENTS_ROW, format!("Achievements ({}/{})", earned, Achievement::ALL.len())),
+        (SHOP_ROW, format!("Shop ({} shards)", save_data.shards)),
+    ];
+    for (i, text) in extras {
+        let color = if i == selected { Color::YELLOW } else { Color::LIGHTGRAY };
+        let y = 100 + i as i32 * LINE_HEIGHT;
+        if i == selected {
+            d.draw_text(">", 40, y, FONT_SIZE, color);
+        }
+        d.draw_text(&text, 60, y, FONT_SIZE, color);
+    }
+
+    d.draw_text("UP/DOWN select, ENTER/A start", 40, screen_size.y as i32 - 30, 16, Color::GRAY);
+}
+
+fn draw_results(
+    d: &mut impl RaylibDraw,
+    mode: GameMode,
+    score: f32,
+    new_best: bool,
+    save_data: &SaveData,
+    screen_size: Vector2,
+) {
+    d.draw_rectangle(0, 0, screen_size.x as i32, screen_size.y as i32, Color::new(0, 0, 0, 200));
+
+    let title = match mode {
+        GameMode::TargetHeight => "TARGET REACHED",
+        GameMode::TimeAttack => "TIME UP",
+        GameMode::RisingDarkness => "SWALLOWED BY THE DARK",
+        GameMode::Endless => "RUN OVER",
+    };
+    d.draw_text(title, 40, 30, 40, Color::WHITE);
+    d.draw_text(mode.name(), 40, 80, FONT_SIZE, Color::LIGHTGRAY);
+    d.draw_text(&format!("Score: {}", mode.format_score(score)), 40, 120, 30, Color::WHITE);
+
+    if new_best {
+        d.draw_text("New best!", 40, 160, FONT_SIZE, Color::GOLD);
+    } else if let Some(best) = save_data.high_score(mode) {
+        d.draw_text(&format!("Best: {}", mode.format_score(best)), 40, 160, FONT_SIZE, Color::GOLD);
+    }
+
+    d.draw_text("ENTER/A play again, ESC/B choose mode", 40, screen_size.y as i32 - 30, 16, Color::GRAY);
+}
+
+fn draw_achievements(d: &mut impl RaylibDraw, save_data: &SaveData, screen_size: Vector2) {
+    d.draw_rectangle(0, 0, screen_size.x as i32, screen_size.y as i32, Color::new(0, 0, 0, 200));
+    d.draw_text("ACHIEVEMENTS", 40, 30, 40, Color::WHITE);
+
+    let stats = &save_data.stats;
+    let accuracy = stats.accuracy().map_or("-".to_string(), |accuracy| format!("{:.0}%", accuracy * 100.0));
+    let lines = [
+        format!("Hooks fired: {}", stats.hooks_fired),
+        format!("Accuracy: {}", accuracy),
+        format!("Distance swung: {:.0}", stats.distance_swung),
+        format!("Falls: {}", stats.falls),
+        format!("Caught in the dark: {}", stats.caught),
+        format!("Time in darkness: {:.0}s", stats.time_in_darkness),
+        format!("Max speed: {:.0}", stats.max_speed),
+        format!("Highest layer: {}", stats.highest_layer),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line, 40, 100 + i as i32 * 28, FONT_SIZE, Color::LIGHTGRAY);
+    }
+
+    let x = screen_size.x as i32 / 2 - 40;
+    for (i, achievement) in Achievement::ALL.iter().enumerate() {
+        let y = 100 + i as i32 * 44;
+        let (marker, color) = if save_data.achievements.has(*achievement) {
+            ("[x]", Color::GOLD)
+        } else {
+            ("[ ]", Color::GRAY)
+        };
+        d.draw_text(&format!("{} {}", marker, achievement.name()), x, y, FONT_SIZE, color);
+        d.draw_text(achievement.description(), x + 36, y + 22, 14, Color::GRAY);
+    }
+
+    d.draw_text("ESC/B back", 40, screen_size.y as i32 - 30, 16, Color::GRAY);
+}
+
+fn draw_shop(d: &mut impl RaylibDraw, selected: usize, save_data: &SaveData, screen_size: Vector2) {
+    d.draw_rectangle(0, 0, screen_size.x as i32, screen_size.y as i32, Color::new(0, 0, 0, 200));
+    d.draw_text("SHOP", 40, 30, 40, Color::WHITE);
+    d.draw_text(&format!("Shards: {}", save_data.shards), 40, 75, FONT_SIZE, Color::GOLD);
+
+    for (i, item) in ShopItem::all().iter().enumerate() {
+        let y = 110 + i as i32 * SHOP_LINE_HEIGHT;
+        // what confirming would do right now
+        let (status, status_color) = match *item {
+            ShopItem::Skin(skin) if save_data.wardrobe.equipped == skin => ("worn".to_string(), Color::GREEN),
+            ShopItem::Skin(skin) if save_data.wardrobe.has(skin) => ("owned".to_string(), Color::LIGHTGRAY),
+            ShopItem::Upgrade(upgrade) if save_data.unlocks.has(upgrade) => ("owned".to_string(), Color::LIGHTGRAY),
+            ShopItem::Upgrade(upgrade) if !can_buy_upgrade(save_data, upgrade) => {
+                ("locked".to_string(), Color::DARKGRAY)
+            }
+            _ if item.price() > save_data.shards => (item.price().to_string(), Color::DARKGRAY),
+            _ => (item.price().to_string(), Color::GOLD),
+        };
+
+        let color = if i == selected { Color::YELLOW } else { Color::LIGHTGRAY };
+        if i == selected {
+            d.draw_text(">", 40, y, FONT_SIZE, color);
+        }
+        if let ShopItem::Skin(skin) = item {
+            d.draw_rectangle(60, y + 3, 14, 14, skin.color());
+        }
+        d.draw_text(&item.name(), 84, y, FONT_SIZE, color);
+        d.draw_text(&status, 360, y, FONT_SIZE, status_color);
+    }
+
+    d.draw_text("ENTER/A buy or wear, ESC/B back", 40, screen_size.y as i32 - 30, 16, Color::GRAY);
+}
+
+fn can_buy_upgrade(save_data: &SaveData, upgrade: Upgrade) -> bool {
+    node(upgrade).requires.is_none_or(|parent| save_data.unlocks.has(parent))
+}
@@ -0,0 +1,94 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::PLAYER_SIZE;
+use crate::progress::height_of;
+
+pub const RECORD_INTERVAL: f32 = 1.0 / 30.0; // seconds between recorded samples
+pub const MAX_SAMPLES: usize = 30 * 60 * 10; // ten minutes, a life longer than that stops recording
+
+const GHOST_COLOR: Color = Color::new(0, 121, 241, 110); // translucent player blue
+const BEST_LINE_COLOR: Color = Color::GOLD;
+const BEST_LINE_HALF_WIDTH: f32 = 2000.0; // wider than any window
+
+// positions of one life sampled every RECORD_INTERVAL
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GhostRun {
+    pub best_height: f32,
+    pub positions: Vec<[f32; 2]>,
+}
+
+impl GhostRun {
+    pub fn duration(&self) -> f32 {
+        self.positions.len().saturating_sub(1) as f32 * RECORD_INTERVAL
+    }
+
+    // interpolated position, None once the run is over
+    pub fn position_at(&self, time: f32) -> Option<Vector2> {
+        if self.positions.is_empty() || time < 0.0 || time > self.duration() {
+            return None;
+        }
+
+        let index = (time / RECORD_INTERVAL) as usize;
+        let from = self.positions[index];
+        let to = self.positions.get(index + 1).copied().unwrap_or(from);
+        let t = (time - index as f32 * RECORD_INTERVAL) / RECORD_INTERVAL;
+
+        Some(Vector2::new(from[0], from[1]).lerp(Vector2::new(to[0], to[1]), t))
+    }
+}
+
+pub struct GhostRecorder {
+    pub run: GhostRun,
+    pub elapsed: f32,
+    next_sample: f32,
+}
+
+impl GhostRecorder {
+    pub fn new() -> Self {
+        Self {
+            run: GhostRun { best_height: f32::MIN, positions: Vec::new() },
+            elapsed: 0.0,
+            next_sample: 0.0,
+        }
+    }
+
+    pub fn record(&mut self, delta_time: f32, position: Vector2) {
+        self.run.best_height = self.run.best_height.max(height_of(position));
+
+        // catch up on every sample point passed this frame so timing stays exact
+        while self.next_sample <= self.elapsed && self.run.positions.len() < MAX_SAMPLES {
+            self.run.positions.push([position.x, position.y]);
+            self.next_sample += RECORD_INTERVAL;
+        }
+        self.elapsed += delta_time;
+    }
+}
+
+impl Default for GhostRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn draw_ghost(d: &mut impl RaylibDraw, position: Vector2) {
+    d.draw_circle_v(position, PLAYER_SIZE, GHOST_COLOR);
+    d.draw_circle_lines(position.x as i32, position.y as i32, PLAYER_SIZE, GHOST_COLOR);
+}
+
+pub fn draw_best_height_line(d: &mut impl RaylibDraw, best_height: f32, center_x: f32) {
+    let y = -best_height;
+    d.draw_line_ex(
+        Vector2::new(center_x - BEST_LINE_HALF_WIDTH, y),
+        Vector2::new(center_x + BEST_LINE_HALF_WIDTH, y),
+        2.0,
+        BEST_LINE_COLOR,
+    );
+    d.draw_text(
+        &format!("BEST {:.0}", best_height),
+        (center_x - 380.0) as i32,
+        (y - 22.0) as i32,
+        20,
+        BEST_LINE_COLOR,
+    );
+}
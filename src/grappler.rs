@@ -1,10 +1,16 @@
 use raylib::prelude::*;
 use crate::object::Object;
 
-const ROPE_LENGTH: f32 = 400.0;
+pub const ROPE_LENGTH: f32 = 400.0;
 const GRAPPLE_SPEED: f32 = 1200.0;
+const REEL_IN_SPEED: f32 = 600.0;
+const RELEASE_BOOST: f32 = 60.0;
+pub const MIN_ROPE_LENGTH: f32 = 30.0; // shortest rope you can reel to while swinging
+const MAX_ASSIST_ANGLE: f32 = 30.0; // half cone in degrees at full aim assist strength
+const CORNER_SNAP_DISTANCE: f32 = 20.0; // snap within 20 pixels
+const AIM_REFRESH_DISTANCE: f32 = 1.0; // aim has to move this far before the target is looked up again
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum GrapplerState {
     Idle,
     Grappling,  // extending
@@ -13,6 +19,59 @@ pub enum GrapplerState {
     Pulling,    // player pulled toward grapple point
 }
 
+#[derive(Clone, Copy)]
+pub struct GrapplerStats {
+    pub rope_length: f32,
+    pub grapple_speed: f32,
+    pub reel_speed: f32,
+    pub release_boost: f32, // extra speed along your motion when letting go
+}
+
+impl Default for GrapplerStats {
+    fn default() -> Self {
+        Self {
+            rope_length: ROPE_LENGTH,
+            grapple_speed: GRAPPLE_SPEED,
+            reel_speed: REEL_IN_SPEED,
+            release_boost: RELEASE_BOOST,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum HookType {
+    Standard,
+    Long,   // reaches further but travels slower
+    Double, // second shot anchors another point
+    Bungee, // elastic rope that launches you
+}
+
+impl HookType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookType::Standard => "Standard",
+            HookType::Long => "Long",
+            HookType::Double => "Double",
+            HookType::Bungee => "Bungee",
+        }
+    }
+
+    pub fn apply(&self, stats: GrapplerStats) -> GrapplerStats {
+        match self {
+            HookType::Standard | HookType::Double => stats,
+            HookType::Long => GrapplerStats {
+                rope_length: stats.rope_length * 1.6,
+                grapple_speed: stats.grapple_speed * 0.6,
+                ..stats
+            },
+            HookType::Bungee => GrapplerStats {
+                reel_speed: stats.reel_speed * 1.5,
+                ..stats
+            },
+        }
+    }
+}
+
 pub struct Grappler {
     pub position: Vector2,
     pub length: f32,
@@ -20,6 +79,33 @@ pub struct Grappler {
     pub state: GrapplerState,
     grapple_direction: Vector2,
     pub release_cooldown: f32, // cooldown seconds
+    pub aim_assist: f32, // 0 = off, 1 = widest cone
+    pub aim_target: Option<AimTarget>,
+    pub predicted_hit: Option<Vector2>,
+    pub base_stats: GrapplerStats, // stats after upgrades, before the hook type
+    pub hook: HookType,
+    pub available_hooks: Vec<HookType>,
+    pub second_point: Option<Vector2>, // double hook's extra anchor
+    pub second_length: f32,
+    pub reel: f32, // -1 lets rope out .. 1 reels in
+    pub rope_bonus: f32, // extra length picked up during this run
+    last_aim: Option<AimLookup>,
+}
+
+// what the last aim lookup was worked out from
+struct AimLookup {
+    origin: Vector2,
+    aim_point: Vector2,
+    assist: f32,
+    range: f32,
+    nearby: Vec<Object>, // streamed, reloaded or removed cubes have to trigger a new lookup too
+}
+
+// corner the aim assist locked on to
+#[derive(Clone, Copy)]
+pub struct AimTarget {
+    pub point: Vector2,
+    pub rect: Rectangle,
 }
 
 pub fn point_in_rect(point: Vector2, rect: Rectangle) -> bool {
@@ -39,24 +125,28 @@ fn get_collision_point(start: Vector2, end: Vector2, objects: &[Object]) -> Opti
     let step = 5.0; // check every 5 pixels
     let num_steps = (len / step) as i32;
 
-    for object in objects {
+    // nearest hit wins so the rope can't pass through one cube to reach another
+    let mut nearest: Option<(i32, Vector2)> = None;
+    for object in objects.iter().filter(|object| object.hookable()) {
         let rect = object.get_rect();
         
         // normal collision detection
         for i in 0..=num_steps {
+            if nearest.is_some_and(|(best, _)| i >= best) {
+                break;
+            }
             let p = start + dir * (i as f32 * step);
             if point_in_rect(p, rect) {
                 // snap to corner after hitting object
-                return Some(snap_to_corner_if_close(p, rect));
+                nearest = Some((i, snap_to_corner_if_close(p, rect)));
+                break;
             }
         }
     }
-    None
+    nearest.map(|(_, point)| point)
 }
 
 fn snap_to_corner_if_close(collision_point: Vector2, rect: Rectangle) -> Vector2 {
-    const CORNER_SNAP_DISTANCE: f32 = 20.0; // snap within 20 pixels
-    
     let top_left = Vector2::new(rect.x, rect.y);
     let top_right = Vector2::new(rect.x + rect.width, rect.y);
     
@@ -74,6 +164,67 @@ fn snap_to_corner_if_close(collision_point: Vector2, rect: Rectangle) -> Vector2
     collision_point
 }
 
+// only cubes the rope could reach are worth raycasting against
+fn objects_in_range(origin: Vector2, range: f32, objects: &[Object]) -> Vec<Object> {
+    objects
+        .iter()
+        .filter(|object| object.hookable() && object.touches_circle(origin, range + CORNER_SNAP_DISTANCE))
+        .copied()
+        .collect()
+}
+
+// grappleable corners are the ones the rope snaps to
+fn grapple_corners(rect: Rectangle) -> [Vector2; 2] {
+    [
+        Vector2::new(rect.x, rect.y),
+        Vector2::new(rect.x + rect.width, rect.y),
+    ]
+}
+
+// corner closest to the aim direction inside the assist cone that the rope can actually reach
+pub fn find_aim_target(
+    origin: Vector2,
+    aim_point: Vector2,
+    objects: &[Object],
+    strength: f32,
+    range: f32,
+) -> Option<AimTarget> {
+    let aim = aim_point - origin;
+    if strength <= 0.0 || aim.length_sqr() == 0.0 {
+        return None;
+    }
+    let aim_dir = aim.normalized();
+    let max_angle = (MAX_ASSIST_ANGLE * strength.min(1.0)).to_radians();
+
+    let nearby = objects_in_range(origin, range, objects);
+    let mut candidates = Vec::new();
+    for object in &nearby {
+        let rect = object.get_rect();
+        for corner in grapple_corners(rect) {
+            let to_corner = corner - origin;
+            let distance = to_corner.length();
+            if distance == 0.0 || distance > range {
+                continue;
+            }
+            let angle = aim_dir.dot(to_corner / distance).clamp(-1.0, 1.0).acos();
+            if angle <= max_angle {
+                candidates.push((angle, corner, rect));
+            }
+        }
+    }
+
+    // closest to the aim first, so usually only one raycast is needed
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+    candidates.into_iter().find_map(|(_, corner, rect)| {
+        // overshoot a little so the stepped raycast doesn't slip past the corner
+        let to_corner = corner - origin;
+        let ray_end = corner + to_corner.normalized() * CORNER_SNAP_DISTANCE;
+        let hit = get_collision_point(origin, ray_end, &nearby);
+        hit.filter(|hit| (*hit - corner).length() <= CORNER_SNAP_DISTANCE)
+            .map(|_| AimTarget { point: corner, rect })
+    })
+}
+
 impl Grappler {
     pub fn new(position: Vector2) -> Self {
         Self {
@@ -83,6 +234,131 @@ impl Grappler {
             state: GrapplerState::Idle,
             grapple_direction: Vector2::zero(),
             release_cooldown: 0.0,
+            aim_assist: 0.0,
+            aim_target: None,
+            predicted_hit: None,
+            base_stats: GrapplerStats::default(),
+            hook: HookType::Standard,
+            available_hooks: vec![HookType::Standard],
+            second_point: None,
+            second_length: 0.0,
+            reel: 0.0,
+            rope_bonus: 0.0,
+            last_aim: None,
+        }
+    }
+
+    pub fn stats(&self) -> GrapplerStats {
+        let mut stats = self.base_stats;
+        stats.rope_length += self.rope_bonus;
+        self.hook.apply(stats)
+    }
+
+    // held reel input changes the rope length, never past the hook's range
+    pub fn apply_reel(&mut self, delta_time: f32) {
+        if self.reel == 0.0 {
+            return;
+        }
+        let stats = self.stats();
+        let length = self.length - self.reel * stats.reel_speed * delta_time;
+        self.length = length.clamp(MIN_ROPE_LENGTH.min(self.length), stats.rope_length.max(self.length));
+    }
+
+    // hooks can only be swapped while the rope is stowed
+    pub fn cycle_hook(&mut self) {
+        if self.state != GrapplerState::Idle || self.available_hooks.is_empty() {
+            return;
+        }
+        let index = self.available_hooks.iter().position(|hook| *hook == self.hook);
+        let next = index.map_or(0, |index| (index + 1) % self.available_hooks.len());
+        self.hook = self.available_hooks[next];
+    }
+
+    // drop every anchor, leaving the grappler ready to fire
+    pub fn release(&mut self, cooldown: f32) {
+        self.state = GrapplerState::Idle;
+        self.length = 0.0;
+        self.second_point = None;
+        self.release_cooldown = cooldown;
+    }
+
+    // the double hook's second shot hits instantly along the aim
+    pub fn fire_second(&mut self, target_pos: Vector2, objects: &[Object]) -> bool {
+        if self.hook != HookType::Double || self.state != GrapplerState::Grappled || self.second_point.is_some() {
+            return false;
+        }
+        let direction = target_pos - self.position;
+        if direction.length_sqr() == 0.0 {
+            return false;
+        }
+        let end = self.position + direction.normalized() * self.stats().rope_length;
+        match get_collision_point(self.position, end, objects) {
+            Some(point) => {
+                self.second_point = Some(point);
+                self.second_length = (point - self.position).length();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // the double hook spends its first press on a second anchor
+    pub fn fire_second_or_reel(&mut self, target_pos: Vector2, objects: &[Object]) {
+        if !self.fire_second(target_pos, objects) {
+            self.state = GrapplerState::Pulling;
+        }
+    }
+
+    // refresh the assist target and predicted hit point while aiming
+    pub fn update_aim(&mut self, origin: Vector2, aim_point: Vector2, objects: &[Object]) {
+        if self.state != GrapplerState::Idle {
+            self.aim_target = None;
+            self.predicted_hit = None;
+            self.last_aim = None;
+            return;
+        }
+
+        // nothing moved and no cube in reach changed since the last lookup, keep the old result
+        let range = self.stats().rope_length;
+        let nearby = objects_in_range(origin, range, objects);
+        let unchanged = self.last_aim.as_ref().is_some_and(|last| {
+            last.origin.distance_to(origin) < AIM_REFRESH_DISTANCE
+                && last.aim_point.distance_to(aim_point) < AIM_REFRESH_DISTANCE
+                && last.assist == self.aim_assist
+                && last.range == range
+                && last.nearby == nearby
+        });
+        if unchanged {
+            return;
+        }
+
+        self.aim_target = find_aim_target(origin, aim_point, &nearby, self.aim_assist, range);
+        let target = self.aim_target.map_or(aim_point, |target| target.point);
+        let direction = target - origin;
+        self.predicted_hit = if direction.length_sqr() > 0.0 {
+            get_collision_point(origin, origin + direction.normalized() * range, &nearby)
+        } else {
+            None
+        };
+        self.last_aim = Some(AimLookup { origin, aim_point, assist: self.aim_assist, range, nearby });
+    }
+
+    // fire at the assisted target if there is one
+    pub fn fire_assisted(&mut self, start_pos: Vector2, target_pos: Vector2) {
+        let target = self.aim_target.map_or(target_pos, |target| target.point);
+        self.fire(start_pos, target);
+    }
+
+    pub fn draw_aim_overlay(&self, d: &mut impl RaylibDraw) {
+        if self.state != GrapplerState::Idle {
+            return;
+        }
+        if let Some(target) = self.aim_target {
+            d.draw_rectangle_lines_ex(target.rect, 2.0, Color::YELLOW);
+        }
+        if let Some(hit) = self.predicted_hit {
+            d.draw_circle_lines(hit.x as i32, hit.y as i32, 5.0, Color::YELLOW);
+            d.draw_circle_v(hit, 2.0, Color::YELLOW);
         }
     }
 
@@ -104,15 +380,29 @@ impl Grappler {
             );
         }
 
+        self.draw_rope(dcam);
+    }
+
+    // where the rope ends, None while it's stowed
+    pub fn rope_end(&self) -> Option<Vector2> {
+        if self.state == GrapplerState::Idle || self.length <= 0.0 {
+            return None;
+        }
+        if self.state == GrapplerState::Grappled || self.state == GrapplerState::Pulling {
+            Some(self.grapple_point)
+        } else {
+            Some(self.position + self.grapple_direction * self.length)
+        }
+    }
+
+    pub fn draw_rope(&self, dcam: &mut impl RaylibDraw) {
         // draw rope if active
-        if self.state != GrapplerState::Idle && self.length > 0.0 {
-            let rope_end = if self.state == GrapplerState::Grappled || self.state == GrapplerState::Pulling {
-                self.grapple_point
-            } else {
-                self.position + self.grapple_direction * self.length
-            };
+        if let Some(rope_end) = self.rope_end() {
             dcam.draw_line_v(self.position, rope_end, Color::BLACK);
         }
+        if let Some(second_point) = self.second_point {
+            dcam.draw_line_v(self.position, second_point, Color::BLACK);
+        }
     }
 
     pub fn fire(&mut self, start_pos: Vector2, target_pos: Vector2) {
@@ -134,9 +424,10 @@ impl Grappler {
         delta_time: f32,
         objects: &[Object],
     ) {
+        let stats = self.stats();
         match self.state {
             GrapplerState::Grappling => {
-                self.length += GRAPPLE_SPEED * delta_time;
+                self.length += stats.grapple_speed * delta_time;
                 let end_point = self.position + self.grapple_direction * self.length;
 
                 if let Some(collision_point) = get_collision_point(self.position, end_point, objects)
@@ -144,13 +435,13 @@ impl Grappler {
                     self.grapple_point = collision_point;
                     self.length = (self.grapple_point - self.position).length();
                     self.state = GrapplerState::Grappled;
-                } else if self.length >= ROPE_LENGTH {
-                    self.length = ROPE_LENGTH;
+                } else if self.length >= stats.rope_length {
+                    self.length = stats.rope_length;
                     self.state = GrapplerState::Retracting;
                 }
             }
             GrapplerState::Retracting => {
-                self.length -= GRAPPLE_SPEED * delta_time;
+                self.length -= stats.grapple_speed * delta_time;
                 if self.length <= 0.0 {
                     self.length = 0.0;
                     self.state = GrapplerState::Idle;
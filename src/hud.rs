@@ -0,0 +1,240 @@
+use raylib::prelude::*;
+
+use crate::biome::Biome;
+use crate::grappler::GrapplerState;
+use crate::input::{Action, InputDevice, prompt};
+use crate::modes::{GameMode, ModeRun, TARGET_HEIGHT};
+use crate::object::Object;
+use crate::player::{Player, PlayerTool};
+use crate::progress::{Progress, height_of};
+use crate::settings::Settings;
+use crate::speedrun::{Speedrun, format_delta, format_time};
+
+const FONT_SIZE: i32 = 20;
+const MARGIN: i32 = 10;
+
+const MINIMAP_SIZE: f32 = 150.0;
+const MINIMAP_SCALE: f32 = 0.1; // minimap pixels per world unit
+
+const ALTITUDE_BAR_WIDTH: i32 = 12;
+const ALTITUDE_BAR_MIN_RANGE: f32 = 1000.0; // bar never shows less than this much height
+
+const ROPE_BAR_WIDTH: i32 = 150;
+const ROPE_BAR_HEIGHT: i32 = 10;
+const MAX_COOLDOWN: f32 = 0.2; // longest cooldown the grappler sets
+
+const TIMER_FONT_SIZE: i32 = 30;
+const AHEAD_COLOR: Color = Color::GREEN;
+const BEHIND_COLOR: Color = Color::RED;
+
+const CURRENT_COLOR: Color = Color::GREEN;
+const BEST_COLOR: Color = Color::GOLD;
+const CHECKPOINT_COLOR: Color = Color::SKYBLUE;
+
+pub fn draw<'a>(
+    d: &mut impl RaylibDraw,
+    player: &Player,
+    progress: &Progress,
+    objects: impl Iterator<Item = &'a Object>,
+    settings: &Settings,
+    device: InputDevice,
+    screen_size: Vector2,
+) {
+    draw_readouts(d, player, progress, settings, device);
+    draw_rope_indicator(d, player, screen_size);
+
+    let minimap_x = screen_size.x as i32 - MINIMAP_SIZE as i32 - MARGIN;
+    draw_minimap(d, player, objects, Vector2::new(minimap_x as f32, MARGIN as f32));
+
+    let bar_top = MARGIN * 2 + MINIMAP_SIZE as i32;
+    let bar_height = screen_size.y as i32 - bar_top - MARGIN;
+    draw_altitude_bar(
+        d,
+        screen_size.x as i32 - MARGIN - ALTITUDE_BAR_WIDTH,
+        bar_top,
+        bar_height,
+        height_of(player.position),
+        progress,
+    );
+}
+
+fn draw_readouts(
+    d: &mut impl RaylibDraw,
+    player: &Player,
+    progress: &Progress,
+    settings: &Settings,
+    device: InputDevice,
+) {
+    let height = height_of(player.position);
+    let speed = player.velocity.length();
+
+    let biome = Biome::at_height(height).name();
+    d.draw_text(&format!("Height: {:.0}  {}", height, biome), MARGIN, 10, FONT_SIZE, CURRENT_COLOR);
+    d.draw_text(&format!("Best: {:.0}", progress.best_height), MARGIN, 35, FONT_SIZE, BEST_COLOR);
+    d.draw_text(&format!("Speed: {:.0}", speed), MARGIN, 60, FONT_SIZE, Color::WHITE);
+
+    // tool indicator
+    // prompts follow whichever device was used last
+    let bindings = &settings.keybindings;
+    let tool_text = match player.current_tool {
+        PlayerTool::Grapple => format!("Tool: Grapple ({})", prompt(Action::GrappleTool, device, bindings)),
+        PlayerTool::Flashlight => format!(
+            "Tool: Flashlight ({}) {:.0}%",
+            prompt(Action::FlashlightTool, device, bindings),
+            player.battery * 100.0
+        ),
+    };
+    d.draw_text(&tool_text, MARGIN, 85, FONT_SIZE, Color::WHITE);
+
+    // darkness toggle status
+    let darkness_text = format!(
+        "Darkness: {} ({} to toggle)",
+        if settings.darkness_enabled { "ON" } else { "OFF" },
+        prompt(Action::ToggleDarkness, device, bindings)
+    );
+    d.draw_text(&darkness_text, MARGIN, 110, FONT_SIZE, Color::WHITE);
+
+    // hook type, only worth showing once there is a choice
+    let grappler = &player.grappler;
+    if grappler.available_hooks.len() > 1 {
+        let hook_text = format!(
+            "Hook: {} ({} to cycle)",
+            grappler.hook.name(),
+            prompt(Action::CycleHook, device, bindings)
+        );
+        d.draw_text(&hook_text, MARGIN, 135, FONT_SIZE, Color::WHITE);
+    }
+}
+
+fn draw_rope_indicator(d: &mut impl RaylibDraw, player: &Player, screen_size: Vector2) {
+    if player.current_tool != PlayerTool::Grapple {
+        return;
+    }
+
+    let grappler = &player.grappler;
+    let x = MARGIN;
+    let y = screen_size.y as i32 - MARGIN - ROPE_BAR_HEIGHT;
+
+    // cooldown takes over the bar until the grappler can fire again
+    let (fill, color, label) = if grappler.release_cooldown > 0.0 {
+        (grappler.release_cooldown / MAX_COOLDOWN, Color::ORANGE, "Cooldown")
+    } else {
+        let label = match grappler.state {
+            GrapplerState::Idle => "Rope ready",
+            GrapplerState::Grappling => "Firing",
+            GrapplerState::Retracting => "Missed",
+            GrapplerState::Grappled => "Swinging",
+            GrapplerState::Pulling => "Reeling in",
+        };
+        (grappler.length / grappler.stats().rope_length, Color::WHITE, label)
+    };
+
+    d.draw_text(label, x, y - FONT_SIZE - 4, FONT_SIZE, color);
+    d.draw_rectangle_lines(x, y, ROPE_BAR_WIDTH, ROPE_BAR_HEIGHT, Color::GRAY);
+    let fill_width = (ROPE_BAR_WIDTH as f32 * fill.clamp(0.0, 1.0)) as i32;
+    d.draw_rectangle(x, y, fill_width, ROPE_BAR_HEIGHT, color);
+}
+
+fn draw_minimap<'a>(
+    d: &mut impl RaylibDraw,
+    player: &Player,
+    objects: impl Iterator<Item = &'a Object>,
+    top_left: Vector2,
+) {
+    let size = MINIMAP_SIZE as i32;
+    d.draw_rectangle(top_left.x as i32, top_left.y as i32, size, size, Color::new(0, 0, 0, 160));
+
+    let center = top_left + Vector2::one() * (MINIMAP_SIZE / 2.0);
+    let bounds = Rectangle::new(top_left.x, top_left.y, MINIMAP_SIZE, MINIMAP_SIZE);
+
+    for object in objects {
+        let rect = object.get_rect();
+        let mapped = Rectangle::new(
+            center.x + (rect.x - player.position.x) * MINIMAP_SCALE,
+            center.y + (rect.y - player.position.y) * MINIMAP_SCALE,
+            (rect.width * MINIMAP_SCALE).max(2.0),
+            (rect.height * MINIMAP_SCALE).max(2.0),
+        );
+        // clip to the minimap instead of drawing over the rest of the hud
+        if let Some(clipped) = mapped.get_collision_rec(&bounds) {
+            d.draw_rectangle_rec(clipped, Color::RED);
+        }
+    }
+
+    d.draw_circle_v(center, 3.0, Color::BLUE);
+    d.draw_rectangle_lines(top_left.x as i32, top_left.y as i32, size, size, Color::GRAY);
+}
+
+fn draw_altitude_bar(d: &mut impl RaylibDraw, x: i32, top: i32, height: i32, current: f32, progress: &Progress) {
+    let checkpoint = progress.checkpoint_height();
+    let range = (progress.best_height.max(current) * 1.1).max(ALTITUDE_BAR_MIN_RANGE);
+
+    d.draw_rectangle(x, top, ALTITUDE_BAR_WIDTH, height, Color::new(0, 0, 0, 160));
+    d.draw_rectangle_lines(x, top, ALTITUDE_BAR_WIDTH, height, Color::GRAY);
+
+    let to_bar_y = |value: f32| -> i32 {
+        let t = (value / range).clamp(0.0, 1.0);
+        top + height - (t * height as f32) as i32
+    };
+
+    let markers = [
+        (checkpoint, CHECKPOINT_COLOR, "CP"),
+        (progress.best_height, BEST_COLOR, "Best"),
+        (current, CURRENT_COLOR, "You"),
+    ];
+    for (value, color, label) in markers {
+        let y = to_bar_y(value);
+        d.draw_rectangle(x - 4, y - 1, ALTITUDE_BAR_WIDTH + 8, 3, color);
+        d.draw_text(label, x - 36, y - 5, 10, color);
+    }
+}
+
+// timer, last split against the personal best and the next milestone, top center
+pub fn draw_speedrun(d: &mut impl RaylibDraw, speedrun: &Speedrun, screen_size: Vector2) {
+    let center_x = screen_size.x as i32 / 2;
+    let timer_color = if speedrun.started { Color::WHITE } else { Color::GRAY };
+    let timer = format_time(speedrun.elapsed);
+    // monospace guess, measure_text needs the raylib handle
+    let timer_width = timer.len() as i32 * TIMER_FONT_SIZE / 2;
+    d.draw_text(&timer, center_x - timer_width / 2, MARGIN, TIMER_FONT_SIZE, timer_color);
+
+    let mut y = MARGIN + TIMER_FONT_SIZE + 4;
+    if let Some(index) = speedrun.splits.len().checked_sub(1) {
+        let split = format!("{:.0}: {}", speedrun.milestone(index), format_time(speedrun.splits[index]));
+        d.draw_text(&split, center_x - 80, y, FONT_SIZE, Color::WHITE);
+        if let Some(delta) = speedrun.delta(index) {
+            let color = if delta < 0.0 { AHEAD_COLOR } else { BEHIND_COLOR };
+            d.draw_text(&format_delta(delta), center_x + 50, y, FONT_SIZE, color);
+        }
+        y += FONT_SIZE + 4;
+    }
+
+    let mut next = format!("Next: {:.0}", speedrun.next_milestone());
+    if let Some(pb) = speedrun.pb_split(speedrun.splits.len()) {
+        next.push_str(&format!(" (PB {})", format_time(pb)));
+    }
+    d.draw_text(&next, center_x - 80, y, FONT_SIZE, Color::GRAY);
+}
+
+// goal of the current mode under the readouts
+pub fn draw_mode(d: &mut impl RaylibDraw, mode_run: &ModeRun, player: &Player) {
+    let text = match mode_run.mode {
+        GameMode::Endless => return,
+        GameMode::TargetHeight => format!("Goal {:.0}: {}", TARGET_HEIGHT, format_time(mode_run.elapsed)),
+        GameMode::TimeAttack => format!("Time left: {}", format_time(mode_run.time_left().unwrap_or(0.0))),
+        GameMode::RisingDarkness => format!("Void: {:.0} below", (mode_run.void_y - player.position.y).max(0.0)),
+    };
+    d.draw_text(&text, MARGIN, 160, FONT_SIZE, Color::VIOLET);
+}
+
+// shards carried toward the shop, under the mode goal
+pub fn draw_shards(d: &mut impl RaylibDraw, shards: u32) {
+    d.draw_text(&format!("Shards: {}", shards), MARGIN, 185, FONT_SIZE, Color::GOLD);
+}
+
+// small and out of the way, for passing to --seed
+pub fn draw_seed(d: &mut impl RaylibDraw, seed: u64, screen_size: Vector2) {
+    let text = format!("seed {}", seed);
+    let x = screen_size.x as i32 / 2 - text.len() as i32 * 4;
+    d.draw_text(&text, x, screen_size.y as i32 - MARGIN - 14, 14, Color::GRAY);
+}
@@ -0,0 +1,545 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// everything the player can bind in the settings menu
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    FastFall,
+    Dash,
+    Fire,
+    ReelIn,
+    ReelOut,
+    GrappleTool,
+    FlashlightTool,
+    CycleHook,
+    ToggleDarkness,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::FastFall,
+        Action::Dash,
+        Action::Fire,
+        Action::ReelIn,
+        Action::ReelOut,
+        Action::GrappleTool,
+        Action::FlashlightTool,
+        Action::CycleHook,
+        Action::ToggleDarkness,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump",
+            Action::FastFall => "Fast fall",
+            Action::Dash => "Air dash",
+            Action::Fire => "Fire / pull",
+            Action::ReelIn => "Reel in",
+            Action::ReelOut => "Reel out",
+            Action::GrappleTool => "Grapple tool",
+            Action::FlashlightTool => "Flashlight tool",
+            Action::CycleHook => "Cycle hook",
+            Action::ToggleDarkness => "Toggle darkness",
+        }
+    }
+}
+
+const KEY_NAMES: &[(&str, KeyboardKey)] = &[
+    ("A", KeyboardKey::KEY_A), ("B", KeyboardKey::KEY_B), ("C", KeyboardKey::KEY_C),
+    ("D", KeyboardKey::KEY_D), ("E", KeyboardKey::KEY_E), ("F", KeyboardKey::KEY_F),
+    ("G", KeyboardKey::KEY_G), ("H", KeyboardKey::KEY_H), ("I", KeyboardKey::KEY_I),
+    ("J", KeyboardKey::KEY_J), ("K", KeyboardKey::KEY_K), ("L", KeyboardKey::KEY_L),
+    ("M", KeyboardKey::KEY_M), ("N", KeyboardKey::KEY_N), ("O", KeyboardKey::KEY_O),
+    ("P", KeyboardKey::KEY_P), ("Q", KeyboardKey::KEY_Q), ("R", KeyboardKey::KEY_R),
+    ("S", KeyboardKey::KEY_S), ("T", KeyboardKey::KEY_T), ("U", KeyboardKey::KEY_U),
+    ("V", KeyboardKey::KEY_V), ("W", KeyboardKey::KEY_W), ("X", KeyboardKey::KEY_X),
+    ("Y", KeyboardKey::KEY_Y), ("Z", KeyboardKey::KEY_Z),
+    ("0", KeyboardKey::KEY_ZERO), ("1", KeyboardKey::KEY_ONE), ("2", KeyboardKey::KEY_TWO),
+    ("3", KeyboardKey::KEY_THREE), ("4", KeyboardKey::KEY_FOUR), ("5", KeyboardKey::KEY_FIVE),
+    ("6", KeyboardKey::KEY_SIX), ("7", KeyboardKey::KEY_SEVEN), ("8", KeyboardKey::KEY_EIGHT),
+    ("9", KeyboardKey::KEY_NINE),
+    ("SPACE", KeyboardKey::KEY_SPACE), ("ENTER", KeyboardKey::KEY_ENTER),
+    ("TAB", KeyboardKey::KEY_TAB), ("BACKSPACE", KeyboardKey::KEY_BACKSPACE),
+    ("UP", KeyboardKey::KEY_UP), ("DOWN", KeyboardKey::KEY_DOWN),
+    ("LEFT", KeyboardKey::KEY_LEFT), ("RIGHT", KeyboardKey::KEY_RIGHT),
+    ("LEFT_SHIFT", KeyboardKey::KEY_LEFT_SHIFT), ("RIGHT_SHIFT", KeyboardKey::KEY_RIGHT_SHIFT),
+    ("LEFT_CONTROL", KeyboardKey::KEY_LEFT_CONTROL), ("RIGHT_CONTROL", KeyboardKey::KEY_RIGHT_CONTROL),
+    ("LEFT_ALT", KeyboardKey::KEY_LEFT_ALT), ("RIGHT_ALT", KeyboardKey::KEY_RIGHT_ALT),
+    ("COMMA", KeyboardKey::KEY_COMMA), ("PERIOD", KeyboardKey::KEY_PERIOD),
+    ("SLASH", KeyboardKey::KEY_SLASH), ("SEMICOLON", KeyboardKey::KEY_SEMICOLON),
+];
+
+const MOUSE_NAMES: &[(&str, MouseButton)] = &[
+    ("MOUSE_LEFT", MouseButton::MOUSE_BUTTON_LEFT),
+    ("MOUSE_RIGHT", MouseButton::MOUSE_BUTTON_RIGHT),
+    ("MOUSE_MIDDLE", MouseButton::MOUSE_BUTTON_MIDDLE),
+];
+
+// a single key or mouse button, stored in the config file by name
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Binding {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binding::Key(key) => KEY_NAMES
+                .iter()
+                .find(|(_, k)| k == key)
+                .map(|(name, _)| *name)
+                .unwrap_or("?"),
+            Binding::Mouse(button) => MOUSE_NAMES
+                .iter()
+                .find(|(_, b)| b == button)
+                .map(|(name, _)| *name)
+                .unwrap_or("?"),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_uppercase();
+        if let Some((_, key)) = KEY_NAMES.iter().find(|(n, _)| *n == name) {
+            return Some(Binding::Key(*key));
+        }
+        MOUSE_NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, button)| Binding::Mouse(*button))
+    }
+
+    // only keys from the name table can be saved, so anything else is rejected
+    pub fn from_key(key: KeyboardKey) -> Option<Self> {
+        KEY_NAMES.iter().find(|(_, k)| *k == key).map(|(_, k)| Binding::Key(*k))
+    }
+
+    pub fn is_down(&self, rl: &RaylibHandle) -> bool {
+        match self {
+            Binding::Key(key) => rl.is_key_down(*key),
+            Binding::Mouse(button) => rl.is_mouse_button_down(*button),
+        }
+    }
+
+    pub fn is_pressed(&self, rl: &RaylibHandle) -> bool {
+        match self {
+            Binding::Key(key) => rl.is_key_pressed(*key),
+            Binding::Mouse(button) => rl.is_mouse_button_pressed(*button),
+        }
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Binding::from_name(&value).ok_or_else(|| format!("unknown key `{}`", value))
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.name().to_string()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    pub move_left: Vec<Binding>,
+    pub move_right: Vec<Binding>,
+    pub jump: Vec<Binding>,
+    pub fast_fall: Vec<Binding>,
+    pub dash: Vec<Binding>,
+    pub fire: Vec<Binding>,
+    pub reel_in: Vec<Binding>,
+    pub reel_out: Vec<Binding>,
+    pub grapple_tool: Vec<Binding>,
+    pub flashlight_tool: Vec<Binding>,
+    pub cycle_hook: Vec<Binding>,
+    pub toggle_darkness: Vec<Binding>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        use KeyboardKey::*;
+        Self {
+            move_left: vec![Binding::Key(KEY_LEFT), Binding::Key(KEY_A)],
+            move_right: vec![Binding::Key(KEY_RIGHT), Binding::Key(KEY_D)],
+            jump: vec![Binding::Key(KEY_SPACE), Binding::Key(KEY_W), Binding::Key(KEY_UP)],
+            fast_fall: vec![Binding::Key(KEY_S), Binding::Key(KEY_DOWN)],
+            dash: vec![Binding::Key(KEY_C)],
+            fire: vec![Binding::Key(KEY_E), Binding::Mouse(MouseButton::MOUSE_BUTTON_LEFT)],
+            reel_in: vec![Binding::Key(KEY_LEFT_SHIFT)],
+            reel_out: vec![Binding::Key(KEY_LEFT_CONTROL)],
+            grapple_tool: vec![Binding::Key(KEY_ONE)],
+            flashlight_tool: vec![Binding::Key(KEY_TWO)],
+            cycle_hook: vec![Binding::Key(KEY_Q)],
+            toggle_darkness: vec![Binding::Key(KEY_F)],
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Jump => &self.jump,
+            Action::FastFall => &self.fast_fall,
+            Action::Dash => &self.dash,
+            Action::Fire => &self.fire,
+            Action::ReelIn => &self.reel_in,
+            Action::ReelOut => &self.reel_out,
+            Action::GrappleTool => &self.grapple_tool,
+            Action::FlashlightTool => &self.flashlight_tool,
+            Action::CycleHook => &self.cycle_hook,
+            Action::ToggleDarkness => &self.toggle_darkness,
+        }
+    }
+
+    // rebinding from the menu replaces every binding for the action
+    pub fn set(&mut self, action: Action, binding: Binding) {
+        let bindings = match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Jump => &mut self.jump,
+            Action::FastFall => &mut self.fast_fall,
+            Action::Dash => &mut self.dash,
+            Action::Fire => &mut self.fire,
+            Action::ReelIn => &mut self.reel_in,
+            Action::ReelOut => &mut self.reel_out,
+            Action::GrappleTool => &mut self.grapple_tool,
+            Action::FlashlightTool => &mut self.flashlight_tool,
+            Action::CycleHook => &mut self.cycle_hook,
+            Action::ToggleDarkness => &mut self.toggle_darkness,
+        };
+        *bindings = vec![binding];
+    }
+
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<&str> = self.get(action).iter().map(|b| b.name()).collect();
+        if names.is_empty() {
+            "unbound".to_string()
+        } else {
+            names.join(" / ")
+        }
+    }
+
+    pub fn is_down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.get(action).iter().any(|b| b.is_down(rl))
+    }
+
+    pub fn is_pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.get(action).iter().any(|b| b.is_pressed(rl))
+    }
+}
+
+const STICK_DEADZONE: f32 = 0.25;
+const TRIGGER_THRESHOLD: f32 = 0.5; // triggers rest at -1 and go to 1
+const STICK_AIM_DISTANCE: f32 = 100.0; // how far ahead of the player stick aim points
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum InputDevice {
+    KeyboardMouse,
+    Gamepad,
+}
+
+// name of the gamepad control for an action, used for on-screen prompts
+pub fn gamepad_prompt(action: Action) -> &'static str {
+    match action {
+        Action::MoveLeft | Action::MoveRight => "L-stick",
+        Action::Jump => "A",
+        Action::FastFall => "L-stick down",
+        Action::Dash => "R3",
+        Action::Fire => "RT",
+        Action::ReelIn => "LT",
+        Action::ReelOut => "B",
+        Action::GrappleTool => "LB",
+        Action::FlashlightTool => "RB",
+        Action::CycleHook => "X",
+        Action::ToggleDarkness => "Y",
+    }
+}
+
+// prompt for whichever device was used last
+pub fn prompt(action: Action, device: InputDevice, bindings: &Keybindings) -> String {
+    match device {
+        InputDevice::KeyboardMouse => bindings.describe(action),
+        InputDevice::Gamepad => gamepad_prompt(action).to_string(),
+    }
+}
+
+// one frame of player input, decoupled from the raylib handle
+#[derive(Clone, Copy, Default)]
+pub struct InputState {
+    pub move_x: f32, // -1 left .. 1 right, analog on a stick
+    pub jump: bool,
+    pub jump_pressed: bool, // only on the frame jump goes down
+    pub fast_fall: bool,
+    pub dash: bool,
+    pub fire: bool,
+    pub reel: f32, // -1 lets rope out .. 1 reels in, analog on a trigger
+    pub select_grapple: bool,
+    pub select_flashlight: bool,
+    pub cycle_hook: bool,
+    pub toggle_darkness: bool,
+    pub aim_target: Vector2, // world space
+}
+
+impl InputState {
+    // anything that moves the player, aiming alone doesn't count
+    pub fn any_action(&self) -> bool {
+        self.move_x != 0.0 || self.jump || self.fast_fall || self.dash || self.fire || self.reel != 0.0
+    }
+}
+
+// reads keyboard/mouse and one gamepad into an InputState
+pub struct InputReader {
+    pub gamepad: i32,
+    pub keyboard: bool, // split-screen players each get one device
+    pub use_gamepad: bool,
+    pub device: InputDevice,
+    stick_aim: Vector2,
+    fire_trigger_down: bool,
+}
+
+fn stick(rl: &RaylibHandle, gamepad: i32, x_axis: GamepadAxis, y_axis: GamepadAxis) -> Vector2 {
+    let value = Vector2::new(
+        rl.get_gamepad_axis_movement(gamepad, x_axis),
+        rl.get_gamepad_axis_movement(gamepad, y_axis),
+    );
+    if value.length() < STICK_DEADZONE {
+        Vector2::zero()
+    } else {
+        value
+    }
+}
+
+impl InputReader {
+    pub fn new(gamepad: i32) -> Self {
+        Self {
+            gamepad,
+            keyboard: true,
+            use_gamepad: true,
+            device: InputDevice::KeyboardMouse,
+            stick_aim: Vector2::new(1.0, 0.0),
+            fire_trigger_down: false,
+        }
+    }
+
+    pub fn keyboard_only() -> Self {
+        Self { use_gamepad: false, ..Self::new(0) }
+    }
+
+    pub fn gamepad_only(gamepad: i32) -> Self {
+        Self { keyboard: false, device: InputDevice::Gamepad, ..Self::new(gamepad) }
+    }
+
+    pub fn poll(
+        &mut self,
+        rl: &RaylibHandle,
+        bindings: &Keybindings,
+        mouse_world: Vector2,
+        player_position: Vector2,
+    ) -> InputState {
+        let mut state = if self.keyboard {
+            self.poll_keyboard(rl, bindings, mouse_world)
+        } else {
+            InputState { aim_target: mouse_world, ..Default::default() }
+        };
+
+        if self.use_gamepad && rl.is_gamepad_available(self.gamepad) {
+            self.poll_gamepad(rl, &mut state);
+        }
+
+        if self.device == InputDevice::Gamepad {
+            state.aim_target = player_position + self.stick_aim * STICK_AIM_DISTANCE;
+        }
+        state
+    }
+
+    fn poll_keyboard(&mut self, rl: &RaylibHandle, bindings: &Keybindings, mouse_world: Vector2) -> InputState {
+        // mouse buttons fire while held, keys only on press
+        let fire = bindings.get(Action::Fire).iter().any(|b| match b {
+            Binding::Key(_) => b.is_pressed(rl),
+            Binding::Mouse(_) => b.is_down(rl),
+        });
+
+        let mut move_x = 0.0;
+        if bindings.is_down(rl, Action::MoveLeft) {
+            move_x -= 1.0;
+        }
+        if bindings.is_down(rl, Action::MoveRight) {
+            move_x += 1.0;
+        }
+
+        let mut reel = 0.0;
+        if bindings.is_down(rl, Action::ReelIn) {
+            reel += 1.0;
+        }
+        if bindings.is_down(rl, Action::ReelOut) {
+            reel -= 1.0;
+        }
+
+        let state = InputState {
+            move_x,
+            jump: bindings.is_down(rl, Action::Jump),
+            jump_pressed: bindings.is_pressed(rl, Action::Jump),
+            fast_fall: bindings.is_down(rl, Action::FastFall),
+            dash: bindings.is_pressed(rl, Action::Dash),
+            fire,
+            reel,
+            select_grapple: bindings.is_pressed(rl, Action::GrappleTool),
+            select_flashlight: bindings.is_pressed(rl, Action::FlashlightTool),
+            cycle_hook: bindings.is_pressed(rl, Action::CycleHook),
+            toggle_darkness: bindings.is_pressed(rl, Action::ToggleDarkness),
+            aim_target: mouse_world,
+        };
+
+        let keyboard_used = state.move_x != 0.0
+            || state.reel != 0.0
+            || state.jump
+            || state.fast_fall
+            || fire
+            || rl.get_mouse_delta().length_sqr() > 0.0;
+        if keyboard_used {
+            self.device = InputDevice::KeyboardMouse;
+        }
+        state
+    }
+
+    fn poll_gamepad(&mut self, rl: &RaylibHandle, state: &mut InputState) {
+        use GamepadButton::*;
+        let pad = self.gamepad;
+        let pressed = |button| rl.is_gamepad_button_pressed(pad, button);
+        let down = |button| rl.is_gamepad_button_down(pad, button);
+
+        let left = stick(rl, pad, GamepadAxis::GAMEPAD_AXIS_LEFT_X, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
+        let right = stick(rl, pad, GamepadAxis::GAMEPAD_AXIS_RIGHT_X, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y);
+
+        // triggers are axes, so edges have to be tracked by hand
+        let fire_trigger =
+            rl.get_gamepad_axis_movement(pad, GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER) > TRIGGER_THRESHOLD;
+        let fire = fire_trigger && !self.fire_trigger_down;
+        self.fire_trigger_down = fire_trigger;
+
+        // left trigger reels in by how far it is pressed
+        let reel_in = (rl.get_gamepad_axis_movement(pad, GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER) + 1.0) / 2.0;
+        let reel_in = if reel_in > STICK_DEADZONE { reel_in } else { 0.0 };
+        let reel = if down(GAMEPAD_BUTTON_RIGHT_FACE_RIGHT) { reel_in - 1.0 } else { reel_in };
+
+        let mut move_x = left.x;
+        if down(GAMEPAD_BUTTON_LEFT_FACE_LEFT) {
+            move_x = -1.0;
+        } else if down(GAMEPAD_BUTTON_LEFT_FACE_RIGHT) {
+            move_x = 1.0;
+        }
+        let jump = down(GAMEPAD_BUTTON_RIGHT_FACE_DOWN);
+        let fast_fall = left.y > 0.5 || down(GAMEPAD_BUTTON_LEFT_FACE_DOWN);
+
+        let face_buttons = [
+            GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+            GAMEPAD_BUTTON_RIGHT_FACE_UP,
+            GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+            GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+            GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+            GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+            GAMEPAD_BUTTON_RIGHT_THUMB,
+        ];
+        let any_gamepad = move_x != 0.0
+            || right.length_sqr() > 0.0
+            || fire_trigger
+            || reel != 0.0
+            || face_buttons.iter().any(|button| down(*button));
+        if any_gamepad {
+            self.device = InputDevice::Gamepad;
+        }
+
+        if right.length_sqr() > 0.0 {
+            self.stick_aim = right.normalized();
+        }
+
+        if move_x != 0.0 {
+            state.move_x = move_x.clamp(-1.0, 1.0);
+        }
+        state.jump |= jump;
+        state.jump_pressed |= pressed(GAMEPAD_BUTTON_RIGHT_FACE_DOWN);
+        state.fast_fall |= fast_fall;
+        state.dash |= pressed(GAMEPAD_BUTTON_RIGHT_THUMB);
+        state.fire |= fire;
+        if reel != 0.0 {
+            state.reel = reel.clamp(-1.0, 1.0);
+        }
+        state.select_grapple |= pressed(GAMEPAD_BUTTON_LEFT_TRIGGER_1);
+        state.select_flashlight |= pressed(GAMEPAD_BUTTON_RIGHT_TRIGGER_1);
+        state.cycle_hook |= pressed(GAMEPAD_BUTTON_RIGHT_FACE_LEFT);
+        state.toggle_darkness |= pressed(GAMEPAD_BUTTON_RIGHT_FACE_UP);
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+// menu navigation from the keyboard or any connected gamepad
+pub fn menu_input(rl: &RaylibHandle) -> Option<MenuInput> {
+    use GamepadButton::*;
+    let keys = [
+        (KeyboardKey::KEY_UP, MenuInput::Up),
+        (KeyboardKey::KEY_DOWN, MenuInput::Down),
+        (KeyboardKey::KEY_LEFT, MenuInput::Left),
+        (KeyboardKey::KEY_RIGHT, MenuInput::Right),
+        (KeyboardKey::KEY_ENTER, MenuInput::Confirm),
+        (KeyboardKey::KEY_ESCAPE, MenuInput::Back),
+    ];
+    let buttons = [
+        (GAMEPAD_BUTTON_LEFT_FACE_UP, MenuInput::Up),
+        (GAMEPAD_BUTTON_LEFT_FACE_DOWN, MenuInput::Down),
+        (GAMEPAD_BUTTON_LEFT_FACE_LEFT, MenuInput::Left),
+        (GAMEPAD_BUTTON_LEFT_FACE_RIGHT, MenuInput::Right),
+        (GAMEPAD_BUTTON_RIGHT_FACE_DOWN, MenuInput::Confirm),
+        (GAMEPAD_BUTTON_RIGHT_FACE_RIGHT, MenuInput::Back),
+        (GAMEPAD_BUTTON_MIDDLE_RIGHT, MenuInput::Back),
+    ];
+
+    if let Some((_, input)) = keys.iter().find(|(key, _)| rl.is_key_pressed(*key)) {
+        return Some(*input);
+    }
+    for gamepad in 0..4 {
+        if !rl.is_gamepad_available(gamepad) {
+            continue;
+        }
+        if let Some((_, input)) = buttons
+            .iter()
+            .find(|(button, _)| rl.is_gamepad_button_pressed(gamepad, *button))
+        {
+            return Some(*input);
+        }
+    }
+    None
+}
+
+// escape or start opens the settings menu
+pub fn menu_requested(rl: &RaylibHandle) -> bool {
+    rl.is_key_pressed(KeyboardKey::KEY_ESCAPE)
+        || (0..4).any(|gamepad| {
+            rl.is_gamepad_available(gamepad)
+                && rl.is_gamepad_button_pressed(gamepad, GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT)
+        })
+}
@@ -1,14 +1,68 @@
 // cubes in specific layer (each layer = 100 units)
-use crate::object::Object;
+use crate::biome::Biome;
+use crate::object::{Object, ObjectKind};
+use crate::progress::height_of;
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
 use super::seeded_random_range;
 
+// how crowded generated layers are
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum LayerProfile {
+    Sparse,
+    #[default]
+    Normal,
+    Dense,
+}
+
+impl LayerProfile {
+    pub fn cubes_per_layer(&self) -> usize {
+        match self {
+            LayerProfile::Sparse => 2,
+            LayerProfile::Normal => 3,
+            LayerProfile::Dense => 5,
+        }
+    }
+}
+
+// hand placed platforms around the spawn point
+pub fn starting_objects() -> Vec<Object> {
+    vec![
+        Object::new(Vector2::new(50.0, 400.0), Vector2::new(300.0, 50.0)), // ground platform
+        Object::new(Vector2::new(300.0, 300.0), Vector2::new(150.0, 20.0)), // platform
+    ]
+}
+
+// the biome at this height picks how crowded the layer is and what's dangerous
 pub fn generate_layer(seed: u64, layer_index: i32) -> Vec<Object> {
+    let profile = Biome::at_height(height_of(Vector2::new(0.0, layer_index as f32))).theme().profile;
+    generate_biome_layer(seed, layer_index, profile)
+}
+
+// cubes in the given layout, with the biome's share of hazards and unhookable ones
+pub fn generate_biome_layer(seed: u64, layer_index: i32, profile: LayerProfile) -> Vec<Object> {
+    let theme = Biome::at_height(height_of(Vector2::new(0.0, layer_index as f32))).theme();
+    let mut cubes = generate_profile_layer(seed, layer_index, profile);
+
+    for (i, cube) in cubes.iter_mut().enumerate() {
+        let kind_seed = seed ^ (layer_index as u64).wrapping_mul(31).wrapping_add(i as u64 * 29);
+        let roll = seeded_random_range(kind_seed, 0.0, 1.0);
+        if roll < theme.hazard_chance {
+            cube.kind = ObjectKind::Hazard;
+        } else if roll < theme.hazard_chance + theme.unhookable_chance {
+            cube.kind = ObjectKind::Unhookable;
+        }
+    }
+    cubes
+}
+
+pub fn generate_profile_layer(seed: u64, layer_index: i32, profile: LayerProfile) -> Vec<Object> {
     // seed + layer for unique stable seed
     let layer_seed = seed ^ (layer_index as u64).wrapping_mul(0x9E3779B97F4A7C15); // golden ratio
 
-    let mut cubes: Vec<Object> = Vec::with_capacity(3);
-    for i in 0..3 {
+    let count = profile.cubes_per_layer();
+    let mut cubes: Vec<Object> = Vec::with_capacity(count);
+    for i in 0..count {
         // different seeds for each cube and axis
         let x_seed = layer_seed.wrapping_mul(13).wrapping_add((i as u64) * 17);
         let y_seed = layer_seed.wrapping_mul(19).wrapping_add((i as u64) * 23);
@@ -22,6 +76,11 @@ pub fn generate_layer(seed: u64, layer_index: i32) -> Vec<Object> {
     cubes
 }
 
+// how many layers up from the first one at y=200, layers are 200 apart
+pub fn layer_number(y: f32) -> i32 {
+    ((200.0 - y) / 200.0).floor().max(0.0) as i32
+}
+
 pub fn layers_at_y(y: f32) -> Vec<i32> {
     let mut layers = Vec::new();
     
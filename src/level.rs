@@ -0,0 +1,409 @@
+use std::fmt;
+use std::path::Path;
+
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::layergen::{LayerProfile, generate_biome_layer, starting_objects};
+use crate::object::{Object, ObjectKind};
+use crate::progress::{CHECKPOINT_RADIUS, START_POSITION};
+
+// bump when the layout changes, and teach migrate() the old one
+pub const LEVEL_VERSION: u32 = 1;
+pub const DEFAULT_LIGHT_RADIUS: f32 = 150.0;
+const PROCEDURAL_GAP: f32 = 200.0; // clear space between the tower top and generated layers
+const CHECKPOINT_COLOR: Color = Color::LIME;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LevelObject {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    #[serde(default)]
+    pub kind: ObjectKind,
+}
+
+impl LevelObject {
+    pub fn from_object(object: &Object) -> Self {
+        Self {
+            position: [object.position.x, object.position.y],
+            size: [object.size.x, object.size.y],
+            kind: object.kind,
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        Object::new(
+            Vector2::new(self.position[0], self.position[1]),
+            Vector2::new(self.size[0], self.size[1]),
+        )
+        .with_kind(self.kind)
+    }
+}
+
+// a lamp that cuts through the darkness
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LevelLight {
+    pub position: [f32; 2],
+    pub radius: f32,
+}
+
+// generated layers between two heights, y values like everything else
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProceduralSection {
+    pub bottom: f32,
+    #[serde(default)]
+    pub top: Option<f32>, // None keeps going forever
+    #[serde(default)]
+    pub seed: Option<u64>, // None uses the run's seed
+    #[serde(default)]
+    pub profile: LayerProfile,
+}
+
+impl ProceduralSection {
+    pub fn contains(&self, y: f32) -> bool {
+        y < self.bottom && self.top.is_none_or(|top| y >= top)
+    }
+}
+
+// a hand-authored tower, built in the editor
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    pub version: u32,
+    pub spawn: [f32; 2],
+    #[serde(default)]
+    pub objects: Vec<LevelObject>,
+    #[serde(default)]
+    pub lights: Vec<LevelLight>,
+    #[serde(default)]
+    pub checkpoints: Vec<[f32; 2]>,
+    #[serde(default)]
+    pub procedural: Vec<ProceduralSection>,
+}
+
+// the layout before versions, written by the first editor
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelV0 {
+    spawn: [f32; 2],
+    objects: Vec<LevelObject>,
+    checkpoints: Vec<[f32; 2]>,
+    procedural_above: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LevelError {
+    pub line: usize, // 1 based, 0 when the problem isn't tied to a line
+    pub message: String,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl From<serde_json::Error> for LevelError {
+    fn from(err: serde_json::Error) -> Self {
+        // serde puts "at line x column y" on the end, we already say the line up front
+        let message = err.to_string();
+        let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+        Self { line: err.line(), message }
+    }
+}
+
+// where a value sits in the file, for error lines
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+// walks already valid json without building it, just far enough to find a path
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn string(&mut self) -> &str {
+        let start = self.pos + 1;
+        self.pos = start;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            match b {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        std::str::from_utf8(&self.bytes[start..self.pos - 1]).unwrap_or("")
+    }
+
+    fn skip_value(&mut self) {
+        match self.peek() {
+            Some(b'"') => {
+                self.string();
+            }
+            Some(open @ (b'{' | b'[')) => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.pos += 1;
+                while let Some(b) = self.peek() {
+                    if b == close {
+                        self.pos += 1;
+                        return;
+                    }
+                    if b == b',' || b == b':' {
+                        self.pos += 1;
+                    }
+                    self.skip_value();
+                }
+            }
+            _ => {
+                while self.bytes.get(self.pos).is_some_and(|b| !b",]}".contains(b) && !b.is_ascii_whitespace()) {
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn find(&mut self, path: &[Step]) -> Option<usize> {
+        let Some(step) = path.first() else {
+            self.peek();
+            return Some(self.pos);
+        };
+        match (step, self.peek()?) {
+            (Step::Key(key), b'{') => {
+                self.pos += 1;
+                while self.peek()? == b'"' {
+                    let name = self.string() == *key;
+                    self.peek();
+                    self.pos += 1; // colon
+                    if name {
+                        return self.find(&path[1..]);
+                    }
+                    self.skip_value();
+                    if self.peek()? == b',' {
+                        self.pos += 1;
+                    }
+                }
+                None
+            }
+            (Step::Index(index), b'[') => {
+                self.pos += 1;
+                for i in 0.. {
+                    if self.peek()? == b']' {
+                        return None;
+                    }
+                    if i == *index {
+                        return self.find(&path[1..]);
+                    }
+                    self.skip_value();
+                    if self.peek()? == b',' {
+                        self.pos += 1;
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+fn line_of(text: &str, path: &[Step]) -> usize {
+    let mut scanner = Scanner { bytes: text.as_bytes(), pos: 0 };
+    match scanner.find(path) {
+        Some(offset) => text[..offset].matches('\n').count() + 1,
+        None => 0,
+    }
+}
+
+// the player's centre can't start inside geometry
+fn inside_any(objects: &[LevelObject], point: [f32; 2]) -> bool {
+    objects.iter().any(|object| {
+        (0..2).all(|axis| point[axis] > object.position[axis] && point[axis] < object.position[axis] + object.size[axis])
+    })
+}
+
+impl Level {
+    // starts from the same platforms as the procedural world
+    pub fn new() -> Self {
+        Self {
+            version: LEVEL_VERSION,
+            spawn: [START_POSITION.x, START_POSITION.y],
+            objects: starting_objects().iter().map(LevelObject::from_object).collect(),
+            lights: Vec::new(),
+            checkpoints: Vec::new(),
+            procedural: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Level, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path, err))?;
+        Level::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn parse(text: &str) -> Result<Level, LevelError> {
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        let newer = |version: u64| LevelError {
+            line: line_of(text, &[Step::Key("version")]),
+            message: format!("version {} is newer than this game understands ({})", version, LEVEL_VERSION),
+        };
+        let version = match value.get("version") {
+            None => 0,
+            Some(version) => {
+                let version = version.as_u64().ok_or_else(|| LevelError {
+                    line: line_of(text, &[Step::Key("version")]),
+                    message: "version must be a whole number".to_string(),
+                })?;
+                // too big for a u32 is still just a version from the future
+                u32::try_from(version).map_err(|_| newer(version))?
+            }
+        };
+
+        let level = match version {
+            0 => Level::migrate(serde_json::from_str(text)?),
+            LEVEL_VERSION => serde_json::from_str(text)?,
+            _ => return Err(newer(version as u64)),
+        };
+        level.validate(text)?;
+        Ok(level)
+    }
+
+    fn migrate(old: LevelV0) -> Level {
+        let mut level = Level {
+            version: LEVEL_VERSION,
+            spawn: old.spawn,
+            objects: old.objects,
+            lights: Vec::new(),
+            checkpoints: old.checkpoints,
+            procedural: Vec::new(),
+        };
+        if old.procedural_above {
+            level.procedural.push(level.section_above());
+        }
+        level
+    }
+
+    // the things serde can't check, each error points at the offending value
+    fn validate(&self, text: &str) -> Result<(), LevelError> {
+        let error = |path: &[Step], message: String| Err(LevelError { line: line_of(text, path), message });
+
+        for (i, object) in self.objects.iter().enumerate() {
+            if object.size[0] <= 0.0 || object.size[1] <= 0.0 {
+                return error(
+                    &[Step::Key("objects"), Step::Index(i), Step::Key("size")],
+                    format!("object {} needs a positive size, got {:?}", i, object.size),
+                );
+            }
+        }
+        if inside_any(&self.objects, self.spawn) {
+            return error(&[Step::Key("spawn")], "spawn is inside an object".to_string());
+        }
+        for (i, checkpoint) in self.checkpoints.iter().enumerate() {
+            if inside_any(&self.objects, *checkpoint) {
+                return error(
+                    &[Step::Key("checkpoints"), Step::Index(i)],
+                    format!("checkpoint {} is inside an object", i),
+                );
+            }
+        }
+        for (i, light) in self.lights.iter().enumerate() {
+            if light.radius <= 0.0 {
+                return error(
+                    &[Step::Key("lights"), Step::Index(i), Step::Key("radius")],
+                    format!("light {} needs a positive radius, got {}", i, light.radius),
+                );
+            }
+        }
+        for (i, section) in self.procedural.iter().enumerate() {
+            if section.top.is_some_and(|top| top >= section.bottom) {
+                return error(
+                    &[Step::Key("procedural"), Step::Index(i), Step::Key("top")],
+                    format!("procedural section {} has its top below its bottom (y grows downward)", i),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if let Some(parent) = Path::new(path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|err| format!("could not create {}: {}", parent.display(), err))?;
+        }
+        std::fs::write(path, self.to_json()).map_err(|err| format!("could not write {}: {}", path, err))
+    }
+
+    pub fn spawn(&self) -> Vector2 {
+        Vector2::new(self.spawn[0], self.spawn[1])
+    }
+
+    pub fn objects(&self) -> Vec<Object> {
+        self.objects.iter().map(LevelObject::to_object).collect()
+    }
+
+    pub fn checkpoints(&self) -> Vec<Vector2> {
+        self.checkpoints.iter().map(|point| Vector2::new(point[0], point[1])).collect()
+    }
+
+    // y of the highest object, the spawn if there are none
+    pub fn top(&self) -> f32 {
+        self.objects.iter().map(|object| object.position[1]).fold(self.spawn[1], f32::min)
+    }
+
+    // endless generated layers from just above the tower
+    pub fn section_above(&self) -> ProceduralSection {
+        ProceduralSection {
+            bottom: self.top() - PROCEDURAL_GAP,
+            top: None,
+            seed: None,
+            profile: LayerProfile::Normal,
+        }
+    }
+
+    // None where the level has no generated layers
+    pub fn generate_layer(&self, seed: u64, layer: i32) -> Option<Vec<Object>> {
+        let section = self.procedural.iter().find(|section| section.contains(layer as f32))?;
+        Some(generate_biome_layer(section.seed.unwrap_or(seed), layer, section.profile))
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn draw_checkpoints(d: &mut impl RaylibDraw, checkpoints: &[Vector2], active: Option<Vector2>) {
+    for checkpoint in checkpoints {
+        let reached = active == Some(*checkpoint);
+        let color = if reached { CHECKPOINT_COLOR } else { CHECKPOINT_COLOR.alpha(0.4) };
+        d.draw_circle_lines(checkpoint.x as i32, checkpoint.y as i32, CHECKPOINT_RADIUS, color);
+        // little flag
+        let pole_top = *checkpoint - Vector2::new(0.0, CHECKPOINT_RADIUS * 1.5);
+        d.draw_line_ex(*checkpoint, pole_top, 2.0, color);
+        d.draw_triangle(
+            pole_top,
+            pole_top + Vector2::new(0.0, 8.0),
+            pole_top + Vector2::new(12.0, 4.0),
+            color,
+        );
+    }
+}
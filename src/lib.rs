@@ -1,8 +1,35 @@
+pub mod achievements;
+pub mod audio;
 pub mod player;
+pub mod biome;
+pub mod collectibles;
 pub mod object;
 pub mod light;
+pub mod modes;
+pub mod particles;
+pub mod net;
 pub mod grappler;
 pub mod layergen;
+pub mod level;
+pub mod editor;
+pub mod enemies;
+pub mod events;
+pub mod input;
+pub mod settings;
+pub mod settings_menu;
+pub mod progress;
+pub mod race_state;
+pub mod relay;
+pub mod hud;
+pub mod game_state;
+pub mod ghost;
+pub mod save;
+pub mod shop;
+pub mod sim;
+pub mod speedrun;
+pub mod stats;
+pub mod sprites;
+pub mod upgrades;
 
 use raylib::prelude::*;
 use rand::{rng, Rng};
@@ -1,5 +1,112 @@
 use raylib::prelude::*;
 
+use crate::collectibles::Collectibles;
+use crate::level::LevelLight;
+use crate::particles::ParticlePool;
+use crate::player::Player;
+
+// how far the light reaches in world units, matching the textures drawn in the darkness mask
+pub const GLOW_RADIUS: f32 = 150.0;
+pub const BEAM_LENGTH: f32 = 400.0;
+pub const BEAM_WIDTH: f32 = 200.0;
+pub const PLAYER_LIGHT_SIZE: f32 = 400.0; // light circle size, the texture fades out at GLOW_RADIUS
+
+// every light drawn into the darkness mask besides the player's own, in world space
+pub fn world_lights(lamps: &[LevelLight], particles: &ParticlePool, collectibles: &Collectibles) -> Vec<(Vector2, f32)> {
+    let lamps = lamps.iter().map(|lamp| (Vector2::new(lamp.position[0], lamp.position[1]), lamp.radius));
+    lamps.chain(particles.lights()).chain(collectibles.lights()).collect()
+}
+
+// the darkness mask in screen space, ambient dark with a hole for every light and the player's own
+pub fn draw_darkness(
+    d: &mut impl RaylibDraw,
+    ambient: Color,
+    spots: &[(Vector2, f32)],
+    player: &Player,
+    player_screen_pos: Vector2,
+    light_tex: &Texture2D,
+    beam_tex: &Texture2D,
+) {
+    d.clear_background(ambient);
+
+    // lamps placed in the level, glowing particles and pickups
+    for (position, radius) in spots {
+        d.draw_texture_pro(
+            light_tex,
+            Rectangle::new(0.0, 0.0, light_tex.width as f32, light_tex.height as f32),
+            Rectangle::new(position.x - radius, position.y - radius, radius * 2.0, radius * 2.0),
+            Vector2::zero(),
+            0.0,
+            Color::WHITE,
+        );
+    }
+
+    // lighting based on equipped tool
+    if player.flashlight_on() {
+        let direction = player.flashlight_direction;
+        let angle = direction.y.atan2(direction.x).to_degrees() - 90.0;
+        d.draw_texture_pro(
+            beam_tex,
+            Rectangle::new(0.0, 0.0, beam_tex.width as f32, beam_tex.height as f32),
+            Rectangle::new(player_screen_pos.x, player_screen_pos.y, BEAM_WIDTH, BEAM_LENGTH),
+            Vector2::new(BEAM_WIDTH / 2.0, 0.0), // origin at top center
+            angle,
+            Color::WHITE,
+        );
+    } else {
+        // circular light
+        d.draw_texture_pro(
+            light_tex,
+            Rectangle::new(0.0, 0.0, light_tex.width as f32, light_tex.height as f32),
+            Rectangle::new(
+                player_screen_pos.x - PLAYER_LIGHT_SIZE / 2.0,
+                player_screen_pos.y - PLAYER_LIGHT_SIZE / 2.0,
+                PLAYER_LIGHT_SIZE,
+                PLAYER_LIGHT_SIZE,
+            ),
+            Vector2::zero(),
+            0.0,
+            Color::WHITE,
+        );
+    }
+}
+
+// where light falls this frame in world space, for anything that behaves differently when lit
+pub struct Visibility {
+    pub darkness: bool, // with the darkness off everything counts as lit
+    pub origin: Vector2,
+    pub beam: Option<Vector2>, // flashlight direction, the glow is off while the beam is on
+    pub spots: Vec<(Vector2, f32)>, // lamps and their radius
+}
+
+impl Visibility {
+    pub fn new(player: &Player, spots: Vec<(Vector2, f32)>, darkness: bool) -> Self {
+        let beam = player.flashlight_on().then_some(player.flashlight_direction);
+        Self { darkness, origin: player.position, beam, spots }
+    }
+
+    pub fn in_beam(&self, point: Vector2) -> bool {
+        let Some(direction) = self.beam else {
+            return false;
+        };
+        let offset = point - self.origin;
+        let along = offset.dot(direction);
+        let across = (offset - direction * along).length();
+        // the cone widens to the full beam width at its far end
+        along > 0.0 && along < BEAM_LENGTH && across < along / BEAM_LENGTH * BEAM_WIDTH / 2.0
+    }
+
+    // under a lamp or a glow, the player's own light doesn't count
+    pub fn in_spot(&self, point: Vector2) -> bool {
+        self.spots.iter().any(|(spot, radius)| point.distance_to(*spot) < *radius)
+    }
+
+    pub fn is_lit(&self, point: Vector2) -> bool {
+        let in_glow = self.beam.is_none() && point.distance_to(self.origin) < GLOW_RADIUS;
+        !self.darkness || in_glow || self.in_beam(point) || self.in_spot(point)
+    }
+}
+
 pub fn create_light_tex(size: i32, max_radius: f32, rl: &mut RaylibHandle, rl_thread: &mut RaylibThread) -> Texture2D {
     let mut light_img = Image::gen_image_color(size, size, Color::new(0, 0, 0, 0));
     
@@ -22,7 +129,7 @@ pub fn create_light_tex(size: i32, max_radius: f32, rl: &mut RaylibHandle, rl_th
         }
     }
     
-    let light_tex = rl.load_texture_from_image(rl_thread, &mut light_img);
+    let light_tex = rl.load_texture_from_image(rl_thread, &light_img);
     drop(light_img);
     light_tex.unwrap()
 }
@@ -61,7 +168,7 @@ pub fn create_flashlight_beam_tex(width: i32, height: i32, rl: &mut RaylibHandle
         }
     }
     
-    let beam_tex = rl.load_texture_from_image(rl_thread, &mut beam_img);
+    let beam_tex = rl.load_texture_from_image(rl_thread, &beam_img);
     drop(beam_img);
     beam_tex.unwrap()
 }
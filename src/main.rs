@@ -1,39 +1,163 @@
 use raylib::prelude::*;
 use std::collections::HashMap;
 mod background;
-mod grappler;
-mod layergen;
-mod light;
-mod object;
-mod player;
+mod race;
 
 use background::{generate_tile_layer, tile_layers_at_y};
 use darkswing::{
-    circle_radius_for_rect, get_position_over_time, random_range, seeded_random_range, with_drawing,
+    achievements, audio, biome, collectibles, editor, enemies, events, game_state, ghost, hud, input,
+    layergen, level, light, modes, net, object, particles, player, progress, save, settings,
+    settings_menu, sim, speedrun, sprites, upgrades,
 };
-use layergen::{generate_layer, layers_at_y};
+use darkswing::{circle_radius_for_rect, get_position_over_time, random_range, with_drawing};
+use collectibles::Collectibles;
+use enemies::Enemies;
+use events::{EventQueue, GameEvent};
+use game_state::GameState;
+use input::InputReader;
+use layergen::{generate_layer, layers_at_y, starting_objects};
+use level::Level;
+use modes::{GameMode, ModeRun};
+use net::NetClient;
 use object::Object;
 use player::{PLAYER_SIZE, Player};
+use ghost::GhostRecorder;
+use progress::{Progress, START_POSITION, height_of};
+use save::{SAVE_PATH, SaveData};
+use settings::{SETTINGS_PATH, Settings};
+use settings_menu::{MenuEvent, SettingsMenu};
+use speedrun::{SPLITS_PATH, Speedrun};
 const RESPAWN_TIME: f32 = 3.0;
 const HOLD_TIME: f32 = 0.5; // 100ms hold after full encapsulation
+
+// `--seed <n>` replays a world, so ghosts from earlier runs line up
+fn seed_from_args() -> Option<u64> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--seed")?;
+    args.get(index + 1)?.parse().ok()
+}
+
+// `--mode <endless|target|timed|rising>` skips the mode select screen
+fn mode_from_args() -> Option<GameMode> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--mode")?;
+    GameMode::from_name(args.get(index + 1)?)
+}
+
+// `--players <2-4>` starts a local split-screen race instead
+fn players_from_args() -> Option<usize> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--players")?;
+    args.get(index + 1)?.parse().ok()
+}
+
+// `--edit <path>` opens the level editor, F5 there plays the level
+fn edit_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--edit")?;
+    args.get(index + 1).cloned()
+}
+
+// `--level <path>` plays a hand-authored tower instead of the generated start
+fn level_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--level")?;
+    args.get(index + 1).cloned()
+}
+
+// `--relay <host:port>` races live against everyone on the same seed
+fn relay_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--relay")?;
+    args.get(index + 1).cloned()
+}
+
+// keeps the ghost and speedrun splits of a run that just ended
+fn save_run(save_data: &mut SaveData, seed: u64, recorder: &GhostRecorder, speedrun: &Speedrun, speedrun_enabled: bool) {
+    save_data.record_run(seed, &recorder.run);
+    if speedrun_enabled && speedrun.started {
+        save_data.record_splits(seed, &speedrun.record());
+        speedrun.export(SPLITS_PATH, seed);
+    }
+    // the stats move on every run, so there is always something to write
+    save_data.save(SAVE_PATH);
+}
+
 fn main() {
+    let mut settings = Settings::load(SETTINGS_PATH);
+    let mut settings_menu = SettingsMenu::new();
+    let mut input_reader = InputReader::new(0);
+
     let (mut rl, mut thread) = raylib::init()
-        .size(800, 600)
+        .size(settings.window_width, settings.window_height)
         .title("darkswing raylib-rs")
         .resizable()
         .build();
 
-    rl.set_target_fps(60);
+    settings.apply(&mut rl);
+    rl.set_exit_key(None); // escape opens the settings menu instead
+
+    let seed: u64 =
+        seed_from_args().unwrap_or_else(|| random_range(0.0, 1000000000000000000.0) as u64);
 
-    let seed: u64 = random_range(0.0, 1000000000000000000.0) as u64;
+    // the sounds borrow the device, so it lives for all of main
+    let audio_device = audio::open_device();
+
+    if let Some(players) = players_from_args() {
+        race::run(&mut rl, &mut thread, &settings, seed, players, audio_device.as_ref());
+        return;
+    }
+
+    let level = if let Some(path) = edit_from_args() {
+        match editor::run(&mut rl, &thread, &path) {
+            Some(level) => Some(level),
+            None => return,
+        }
+    } else if let Some(path) = level_from_args() {
+        match Level::load(&path) {
+            Ok(level) => Some(level),
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    let spawn = level.as_ref().map_or(START_POSITION, Level::spawn);
+    let checkpoints = level.as_ref().map_or_else(Vec::new, Level::checkpoints);
+    let lights = level.as_ref().map_or_else(Vec::new, |level| level.lights.clone());
+
+    let mut save_data = SaveData::load(SAVE_PATH);
+    let best_ghost = save_data.ghost(seed).cloned();
+    let mut recorder = GhostRecorder::new();
+    let mut net = relay_from_args().and_then(|relay| match NetClient::connect(&relay, seed) {
+        Ok(client) => Some(client),
+        Err(err) => {
+            eprintln!("could not reach relay {}: {}", relay, err);
+            None
+        }
+    });
+    let mut speedrun = Speedrun::new(settings.split_interval as f32, save_data.splits.get(&seed).cloned());
 
-    let mut camera = Camera2D::default();
-    camera.offset = Vector2::new(400.0, 300.0); // center of screen
-    camera.rotation = 0.0;
-    camera.zoom = 1.0;
+    let mut camera = Camera2D {
+        offset: Vector2::new(400.0, 300.0), // center of screen
+        rotation: 0.0,
+        zoom: 1.0,
+        ..Default::default()
+    };
 
     // player
-    let mut player = Player::new(Vector2::new(100.0, 100.0));
+    let mut player = Player::new(spawn);
+    let mut progress = Progress::starting_at(spawn);
+    progress.best_height = progress.best_height.max(save_data.best_height);
+    save_data.unlocks.apply(&mut player);
+    save_data.wardrobe.apply(&mut player);
+
+    // modes
+    let mut game_state = GameState::ModeSelect { selected: 0 };
+    let mut pending_start = mode_from_args();
+    let mut mode_run = ModeRun::new(GameMode::Endless);
 
     // respawn timer
     let mut is_respawning = false;
@@ -43,35 +167,75 @@ fn main() {
     let mut screen_texture = rl.load_render_texture(&thread, 800, 600).unwrap();
     let mut light_shader = light::only_on_black_shader(&mut rl, &mut thread);
 
-    let mut objects: Vec<Object> = Vec::new();
-    objects.push(Object::new(
-        Vector2::new(50.0, 400.0),
-        Vector2::new(300.0, 50.0),
-    )); // ground platform
-    objects.push(Object::new(
-        Vector2::new(300.0, 300.0),
-        Vector2::new(150.0, 20.0),
-    )); // platform
+    let objects: Vec<Object> = level.as_ref().map_or_else(starting_objects, Level::objects);
 
     let mut layermap: HashMap<i32, Vec<Object>> = HashMap::new();
     let mut tilemap: HashMap<i32, Vec<background::Tile>> = HashMap::new();
+    let mut collectibles = Collectibles::new();
+    let mut enemies = Enemies::new();
 
     let window_size = Vector2::new(800.0, 600.0);
     let mut actual_window_size: Vector2;
 
     let light_tex = light::create_light_tex(800, 300.0, &mut rl, &mut thread);
     let flashlight_beam_tex = light::create_flashlight_beam_tex(200, 400, &mut rl, &mut thread);
-    let mut darkness_mask = rl.load_render_texture(&mut thread, 800, 600).unwrap();
-    let mut darkness_enabled = true; // darkness toggle
+    let mut darkness_mask = rl.load_render_texture(&thread, 800, 600).unwrap();
+    let atlas = sprites::Atlas::load(&mut rl, &thread, sprites::ATLAS_PATH);
+    let mut animator = sprites::Animator::new();
+    let mut particles = particles::ParticlePool::new();
+    let mut effects = particles::PlayerEffects::new();
+    let mut audio = audio::Audio::new(audio_device.as_ref(), &settings);
+    let mut events = EventQueue::new();
+    let mut toasts = achievements::Toasts::new();
     while !rl.window_should_close() {
-        let delta_time = rl.get_frame_time();
-        actual_window_size =
-            Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        // the results, achievements and shop screens use escape to go back to mode select
+        let settings_allowed =
+            !matches!(game_state, GameState::Finished { .. } | GameState::Achievements | GameState::Shop { .. });
+
+        // settings menu pauses the game
+        if settings_menu.open {
+            match settings_menu.update(&mut rl, &mut settings) {
+                Some(MenuEvent::Changed) => {
+                    settings.save(SETTINGS_PATH);
+                    settings.apply(&mut rl);
+                    // a new split interval only applies before the clock starts
+                    if !speedrun.started && speedrun.interval != settings.split_interval as f32 {
+                        speedrun = Speedrun::new(settings.split_interval as f32, save_data.splits.get(&seed).cloned());
+                    }
+                }
+                Some(MenuEvent::Quit) => break,
+                None => {}
+            }
+        } else if settings_allowed && input::menu_requested(&rl) {
+            settings_menu.open = true;
+        } else if let Some(mode) = game_state.update(&rl, &mut save_data) {
+            pending_start = Some(mode);
+        }
 
-        // darkness toggle
-        if rl.is_key_pressed(KeyboardKey::KEY_F) {
-            darkness_enabled = !darkness_enabled;
+        // starting a mode resets the run but keeps unlocks and records
+        if let Some(mode) = pending_start.take() {
+            save_run(&mut save_data, seed, &recorder, &speedrun, settings.speedrun);
+            player = Player::new(spawn);
+            save_data.unlocks.apply(&mut player);
+            save_data.wardrobe.apply(&mut player);
+            progress = Progress::starting_at(spawn);
+            progress.best_height = progress.best_height.max(save_data.best_height);
+            recorder = GhostRecorder::new();
+            speedrun = Speedrun::new(settings.split_interval as f32, save_data.splits.get(&seed).cloned());
+            is_respawning = false;
+            respawn_timer = 0.0;
+            mode_run = ModeRun::new(mode);
+            // everything picked up comes back for the new run
+            collectibles = Collectibles::new();
+            enemies = Enemies::new();
+            layermap.clear();
+            game_state = GameState::Playing;
         }
+        let paused = settings_menu.open || !game_state.is_playing();
+
+        let delta_time = if paused { 0.0 } else { rl.get_frame_time() };
+        actual_window_size =
+            Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
 
         // coordinate conversion
         // destination rectangle for 800x600 texture to maintain aspect ratio
@@ -104,13 +268,13 @@ fn main() {
         // texture-space to world-space
         let mouse_position = rl.get_screen_to_world2D(mouse_texture_pos, camera);
 
-        // combine objects for grappler collision detection
-        let mut all_objects = objects.clone();
-        for layer_objects in layermap.values() {
-            all_objects.extend(layer_objects.iter().cloned());
-        }
+        let input = input_reader.poll(&rl, &settings.keybindings, mouse_position, player.position);
 
-        player.input(delta_time, &mut rl, &all_objects, mouse_position);
+        // darkness toggle
+        if !paused && input.toggle_darkness {
+            settings.darkness_enabled = !settings.darkness_enabled;
+            settings.save(SETTINGS_PATH);
+        }
 
         // update layermap
         let layers = layers_at_y(player.position.y);
@@ -119,11 +283,23 @@ fn main() {
         for layer in old_layers {
             if !layers.contains(layer) {
                 layermap.remove(layer);
+                collectibles.unload_layer(*layer);
+                enemies.unload_layer(*layer);
             }
         }
         for layer in layers {
-            if !layermap.contains_key(&layer) {
-                layermap.insert(layer, generate_layer(seed, layer));
+            if layermap.contains_key(&layer) {
+                continue;
+            }
+            // a level only generates layers inside its procedural sections
+            let generated = match &level {
+                Some(level) => level.generate_layer(seed, layer),
+                None => Some(generate_layer(seed, layer)),
+            };
+            if let Some(layer_objects) = generated {
+                collectibles.load_layer(seed, layer, &layer_objects);
+                enemies.load_layer(seed, layer, &layer_objects);
+                layermap.insert(layer, layer_objects);
             }
         }
 
@@ -136,30 +312,81 @@ fn main() {
             }
         }
         for layer in tile_layers_needed {
-            if !tilemap.contains_key(&layer) {
-                tilemap.insert(layer, generate_tile_layer(seed, layer));
-            }
+            tilemap
+                .entry(layer)
+                .or_insert_with(|| generate_tile_layer(seed, layer));
+        }
+
+        // combine objects for grappler and player collision detection
+        let mut all_objects = objects.clone();
+        for layer_objects in layermap.values() {
+            all_objects.extend(layer_objects.iter().cloned());
         }
 
         // player physics
-        let update_state = player.update(delta_time, is_respawning);
+        if !paused {
+            player.grappler.aim_assist = settings.aim_assist;
+            let update_state = sim::step(&mut player, delta_time, &input, &all_objects, is_respawning, &mut events);
+            recorder.record(delta_time, player.position);
+            animator.update(delta_time, &player);
+            effects.update(delta_time, &player, &mut particles);
+            particles.update(delta_time);
+            if let Some(net) = net.as_mut() {
+                net.send(&player);
+            }
+            if settings.speedrun
+                && speedrun.update(delta_time, &input, height_of(player.position)).is_some()
+                && save_data.record_splits(seed, &speedrun.record())
+            {
+                // a new personal best is written out right away, not only when the game closes
+                speedrun.export(SPLITS_PATH, seed);
+            }
+            if let Some(score) = mode_run.update(delta_time, &input, player.position) {
+                let new_best = save_data.record_score(mode_run.mode, score);
+                save_data.save(SAVE_PATH);
+                game_state = GameState::Finished { mode: mode_run.mode, score, new_best };
+            }
+
+            // what lurks in the dark decides what to do from where the light falls
+            let spots = light::world_lights(&lights, &particles, &collectibles);
+            let visibility = light::Visibility::new(&player, spots, settings.darkness_enabled);
+            save_data.stats.update(delta_time, &player, &visibility);
+            // a real fall on the same tick already ends the life
+            let caught = enemies.update(delta_time, player.position, &visibility) && !is_respawning && !update_state.fell;
+            if caught {
+                events.push(GameEvent::Caught { position: player.position });
+            }
 
-        if update_state.fell {
-            is_respawning = true;
+            if (update_state.fell || caught) && !is_respawning {
+                is_respawning = true;
+                // every lost life is written out, the shards and stats since the last save go with the ghost
+                save_data.record_run(seed, &recorder.run);
+                save_data.save(SAVE_PATH);
+            }
         }
 
-        // collisions with objects after player update
-        for object in objects.iter() {
-            player.update_touch_ground(object);
+        if let Some(net) = net.as_mut() {
+            net.poll();
         }
 
-        // collisions with layermap objects
-        for layer_objects in layermap.values() {
-            for object in layer_objects.iter() {
-                player.update_touch_ground(object);
+        progress.update(&player, &mut events);
+        progress.touch_checkpoints(player.position, &checkpoints, &mut events);
+        if !is_respawning {
+            for kind in collectibles.pick_up(player.position, &mut events) {
+                save_data.shards += kind.apply(&mut player);
             }
         }
 
+        // grappler upgrades unlock as the best height climbs
+        let unlocked = save_data.unlocks.unlock_by_height(progress.best_height);
+        if !unlocked.is_empty() {
+            for upgrade in unlocked {
+                toasts.show(format!("Upgrade: {}", upgrades::node(upgrade).name), "Unlocked by your best height");
+            }
+            save_data.unlocks.apply(&mut player);
+            save_data.save(SAVE_PATH);
+        }
+
         // camera follow player
         camera.target = player.position;
         camera.offset = Vector2::new(window_size.x / 2.0, window_size.y / 2.0); // screen center
@@ -186,7 +413,12 @@ fn main() {
 
             // respawn + hold time completed
             if respawn_timer >= RESPAWN_TIME + HOLD_TIME {
-                player.position = Vector2::new(100.0, 100.0);
+                player.position = mode_run.respawn_point(progress.spawn_point());
+                player.velocity = Vector2::zero();
+                player.battery = 1.0; // a new life starts with a full flashlight
+                events.push(GameEvent::Respawned { position: player.position });
+                // each life is its own ghost, the one that just ended was kept when it fell
+                recorder = GhostRecorder::new();
                 is_respawning = false;
                 respawn_timer = 0.0;
             }
@@ -209,37 +441,49 @@ fn main() {
             );
         }
 
+        // everything that happened this frame has been queued, let the listeners react
+        for event in events.iter() {
+            particles::handle_event(event, &mut particles);
+            audio.mixer.handle_event(event);
+            save_data.stats.handle_event(event);
+            // checkpoints top up the flashlight too
+            if let GameEvent::CheckpointReached { .. } = event {
+                player.battery = 1.0;
+            }
+        }
+        events.clear();
+
+        let earned = save_data.achievements.unlock_from(&save_data.stats);
+        if !earned.is_empty() {
+            for achievement in earned {
+                toasts.unlocked(achievement);
+            }
+            save_data.save(SAVE_PATH);
+        }
+        toasts.update(rl.get_frame_time());
+        audio.update(delta_time, &settings, camera.target);
+
         // player screen position for darkness
         let player_screen_pos = rl.get_world_to_screen2D(player.position, camera);
-        let light_size = 400.0; // light circle size
+        // lamps, glowing particles and pickups, the same lights the enemies look at
+        let level_light_spots: Vec<(Vector2, f32)> = light::world_lights(&lights, &particles, &collectibles)
+            .into_iter()
+            .map(|(position, radius)| (rl.get_world_to_screen2D(position, camera), radius))
+            .collect();
+        let time = rl.get_time() as f32;
 
         with_drawing(
-            &mut rl.begin_texture_mode(&mut thread, &mut screen_texture),
+            &mut rl.begin_texture_mode(&thread, &mut screen_texture),
             |dtex| {
                 with_drawing(&mut dtex.begin_mode2D(camera), |dcam| {
-                    // begin mode 2d
-                    dcam.clear_background(Color::RAYWHITE);
-
-                    // background tiles first
-                    for tile_layer in tilemap.values() {
-                        for tile in tile_layer.iter() {
-                            tile.draw(dcam);
-                        }
-                    }
-
-                    player.draw(dcam);
+                    let level_objects = objects.iter().chain(layermap.values().flatten());
+                    background::draw_world(dcam, seed, camera, window_size, &tilemap, level_objects, atlas.as_ref());
+                    sprites::draw_player(dcam, atlas.as_ref(), &player, &animator);
                     player.draw_grappler(dcam, mouse_position);
 
-                    for object in objects.iter() {
-                        object.draw(dcam);
-                    }
-
-                    // layermap objects
-                    for layer_objects in layermap.values() {
-                        for object in layer_objects.iter() {
-                            object.draw(dcam);
-                        }
-                    }
+                    collectibles.draw(dcam, time);
+                    enemies.draw(dcam, time);
+                    particles.draw(dcam);
                 });
 
                 if is_respawning {
@@ -262,65 +506,21 @@ fn main() {
         );
 
         // darkness mask if enabled
-        if darkness_enabled {
+        if settings.darkness_enabled {
             with_drawing(
-                &mut rl.begin_texture_mode(&mut thread, &mut darkness_mask),
+                &mut rl.begin_texture_mode(&thread, &mut darkness_mask),
                 |dtex| {
-                    dtex.clear_background(Color::BLACK);
-
-                    // lighting based on equipped tool
-                    if player.current_tool == player::PlayerTool::Flashlight {
-                        // flashlight beam
-                        let beam_length = 400.0;
-                        let beam_width = 200.0;
-
-                        // rotation angle from flashlight direction
-                        let angle = player
-                            .flashlight_direction
-                            .y
-                            .atan2(player.flashlight_direction.x)
-                            .to_degrees()
-                            - 90.0;
-
-                        dtex.draw_texture_pro(
-                            &flashlight_beam_tex,
-                            Rectangle::new(
-                                0.0,
-                                0.0,
-                                flashlight_beam_tex.width as f32,
-                                flashlight_beam_tex.height as f32,
-                            ),
-                            Rectangle::new(
-                                player_screen_pos.x,
-                                player_screen_pos.y,
-                                beam_width,
-                                beam_length,
-                            ),
-                            Vector2::new(beam_width / 2.0, 0.0), // origin at top center
-                            angle,
-                            Color::WHITE,
-                        );
-                    } else {
-                        // circular light
-                        dtex.draw_texture_pro(
-                            &light_tex,
-                            Rectangle::new(
-                                0.0,
-                                0.0,
-                                light_tex.width as f32,
-                                light_tex.height as f32,
-                            ),
-                            Rectangle::new(
-                                player_screen_pos.x - light_size / 2.0,
-                                player_screen_pos.y - light_size / 2.0,
-                                light_size,
-                                light_size,
-                            ),
-                            Vector2::zero(),
-                            0.0,
-                            Color::WHITE,
-                        );
-                    }
+                    // higher biomes aren't quite as dark
+                    let ambient = biome::ambient_color(height_of(player.position));
+                    light::draw_darkness(
+                        dtex,
+                        ambient,
+                        &level_light_spots,
+                        &player,
+                        player_screen_pos,
+                        &light_tex,
+                        &flashlight_beam_tex,
+                    );
                 },
             );
         }
@@ -343,7 +543,7 @@ fn main() {
             );
 
             // darkness effect if enabled
-            if darkness_enabled {
+            if settings.darkness_enabled {
                 // darkness mask with multiply blending for "hole" effect
                 with_drawing(&mut d.begin_blend_mode(BlendMode::BLEND_MULTIPLIED), |d| {
                     d.draw_texture_pro(
@@ -362,40 +562,71 @@ fn main() {
                 });
             }
 
-            // UI on top of everything
+            // best height marker, ghost, eyes in the dark and aim highlights stay visible through the darkness
             if !is_respawning {
-                let flipped = if player.position.y != 0.0 {
-                    -player.position.y
-                } else {
-                    0.0
+                let overlay_camera = Camera2D {
+                    offset: Vector2::new(x_offset + dest_width / 2.0, y_offset + dest_height / 2.0),
+                    target: camera.target,
+                    rotation: 0.0,
+                    zoom: dest_width / window_size.x,
                 };
-                if flipped > 0.0 {
-                    d.draw_text(
-                        &format!("Y: {}", flipped.to_string().as_str()),
-                        10,
-                        10,
-                        20,
-                        Color::GREEN,
-                    );
-                } else {
-                    d.draw_text(&format!("Y not found"), 10, 10, 20, Color::RED);
-                }
+                let mut scissor = d.begin_scissor_mode(
+                    x_offset as i32,
+                    y_offset as i32,
+                    dest_width as i32,
+                    dest_height as i32,
+                );
+                with_drawing(&mut scissor.begin_mode2D(overlay_camera), |dcam| {
+                    mode_run.draw_void(dcam, camera.target.x);
+                    enemies.draw_eyes(dcam);
+                    level::draw_checkpoints(dcam, &checkpoints, progress.checkpoint);
+                    if save_data.best_height > 0.0 {
+                        ghost::draw_best_height_line(dcam, save_data.best_height, camera.target.x);
+                    }
+                    let ghost_position =
+                        best_ghost.as_ref().and_then(|run| run.position_at(recorder.elapsed));
+                    if settings.show_ghost && let Some(position) = ghost_position {
+                        ghost::draw_ghost(dcam, position);
+                    }
+                    if let Some(net) = net.as_ref() {
+                        net::draw_peers(dcam, net);
+                    }
+                    if player.current_tool == player::PlayerTool::Grapple {
+                        player.grappler.draw_aim_overlay(dcam);
+                    }
+                });
+            }
 
-                // tool indicator
-                let tool_text = match player.current_tool {
-                    player::PlayerTool::Grapple => "Tool: Grapple (1)",
-                    player::PlayerTool::Flashlight => "Tool: Flashlight (2)",
-                };
-                d.draw_text(tool_text, 10, 40, 20, Color::WHITE);
+            // UI on top of everything
+            if !is_respawning {
+                let nearby_objects = objects.iter().chain(layermap.values().flatten());
+                hud::draw(
+                    d,
+                    &player,
+                    &progress,
+                    nearby_objects,
+                    &settings,
+                    input_reader.device,
+                    actual_window_size,
+                );
+            }
+            // the clock keeps running through respawns
+            if settings.speedrun {
+                hud::draw_speedrun(d, &speedrun, actual_window_size);
+            }
+            hud::draw_mode(d, &mode_run, &player);
+            if game_state.is_playing() {
+                hud::draw_shards(d, save_data.shards);
+                hud::draw_seed(d, seed, actual_window_size);
+            }
+            game_state.draw(d, &save_data, actual_window_size);
+            toasts.draw(d, actual_window_size);
 
-                // darkness toggle status
-                let darkness_text = if darkness_enabled {
-                    "Darkness: ON (F to toggle)"
-                } else {
-                    "Darkness: OFF (F to toggle)"
-                };
-                d.draw_text(darkness_text, 10, 70, 20, Color::WHITE);
+            if settings_menu.open {
+                settings_menu.draw(d, &settings, actual_window_size);
             }
         });
     }
+
+    save_run(&mut save_data, seed, &recorder, &speedrun, settings.speedrun);
 }
@@ -0,0 +1,144 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::input::InputState;
+use crate::player::PLAYER_SIZE;
+use crate::progress::height_of;
+
+pub const TARGET_HEIGHT: f32 = 5000.0;
+pub const TIME_LIMIT: f32 = 120.0; // seconds
+const VOID_START_Y: f32 = 600.0; // just under the starting platform
+const VOID_RISE_SPEED: f32 = 15.0;
+const VOID_RISE_ACCELERATION: f32 = 0.5; // the void speeds up the longer you last
+const VOID_RESPAWN_CLEARANCE: f32 = 300.0; // room above the void to catch a cube after respawning
+const VOID_COLOR: Color = Color::new(20, 0, 30, 230);
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GameMode {
+    Endless,
+    TargetHeight,
+    TimeAttack,
+    RisingDarkness,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Endless,
+        GameMode::TargetHeight,
+        GameMode::TimeAttack,
+        GameMode::RisingDarkness,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::TargetHeight => "Target height",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::RisingDarkness => "Rising darkness",
+        }
+    }
+
+    // used by --mode on the command line
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        match name {
+            "endless" => Some(GameMode::Endless),
+            "target" => Some(GameMode::TargetHeight),
+            "timed" => Some(GameMode::TimeAttack),
+            "rising" => Some(GameMode::RisingDarkness),
+            _ => None,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            GameMode::Endless => "Climb forever".to_string(),
+            GameMode::TargetHeight => format!("Reach {:.0} height as fast as you can", TARGET_HEIGHT),
+            GameMode::TimeAttack => format!("Climb as high as you can in {:.0} seconds", TIME_LIMIT),
+            GameMode::RisingDarkness => "Stay above the void rising from below".to_string(),
+        }
+    }
+
+    // target height scores a time, the rest a height
+    pub fn better(&self, score: f32, best: f32) -> bool {
+        match self {
+            GameMode::TargetHeight => score < best,
+            _ => score > best,
+        }
+    }
+
+    pub fn format_score(&self, score: f32) -> String {
+        match self {
+            GameMode::TargetHeight => crate::speedrun::format_time(score),
+            _ => format!("{:.0}", score),
+        }
+    }
+}
+
+// state of one attempt at a mode
+pub struct ModeRun {
+    pub mode: GameMode,
+    pub started: bool,
+    pub elapsed: f32,
+    pub best_height: f32,
+    pub void_y: f32,
+}
+
+impl ModeRun {
+    pub fn new(mode: GameMode) -> Self {
+        Self {
+            mode,
+            started: false,
+            elapsed: 0.0,
+            best_height: 0.0,
+            void_y: VOID_START_Y,
+        }
+    }
+
+    // the clock starts on the first input, returns the score once the run is over
+    pub fn update(&mut self, delta_time: f32, input: &InputState, position: Vector2) -> Option<f32> {
+        if !self.started {
+            if !input.any_action() {
+                return None;
+            }
+            self.started = true;
+        }
+        self.elapsed += delta_time;
+        self.best_height = self.best_height.max(height_of(position));
+
+        match self.mode {
+            GameMode::Endless => None,
+            GameMode::TargetHeight => (self.best_height >= TARGET_HEIGHT).then_some(self.elapsed),
+            GameMode::TimeAttack => (self.elapsed >= TIME_LIMIT).then_some(self.best_height),
+            GameMode::RisingDarkness => {
+                let speed = VOID_RISE_SPEED + VOID_RISE_ACCELERATION * self.elapsed;
+                self.void_y -= speed * delta_time;
+                (position.y + PLAYER_SIZE > self.void_y).then_some(self.best_height)
+            }
+        }
+    }
+
+    // rising darkness never puts you back under the void
+    pub fn respawn_point(&self, spawn: Vector2) -> Vector2 {
+        if self.mode != GameMode::RisingDarkness {
+            return spawn;
+        }
+        Vector2::new(spawn.x, spawn.y.min(self.void_y - VOID_RESPAWN_CLEARANCE))
+    }
+
+    pub fn time_left(&self) -> Option<f32> {
+        (self.mode == GameMode::TimeAttack).then(|| (TIME_LIMIT - self.elapsed).max(0.0))
+    }
+
+    pub fn draw_void(&self, d: &mut impl RaylibDraw, center_x: f32) {
+        if self.mode != GameMode::RisingDarkness {
+            return;
+        }
+        d.draw_rectangle_rec(Rectangle::new(center_x - 2000.0, self.void_y, 4000.0, 4000.0), VOID_COLOR);
+        d.draw_line_ex(
+            Vector2::new(center_x - 2000.0, self.void_y),
+            Vector2::new(center_x + 2000.0, self.void_y),
+            3.0,
+            Color::PURPLE,
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ghost;
+use crate::player::{PLAYER_SIZE, Player, PlayerTool};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const PEER_TIMEOUT: Duration = Duration::from_secs(5); // silent peers are dropped
+pub const MAX_PACKET_SIZE: usize = 1024;
+pub const SEND_INTERVAL: Duration = Duration::from_millis(50); // 20 states a second, whatever the frame rate
+const ROPE_COLOR: Color = Color::new(0, 0, 0, 110);
+
+// everything the others need to draw you, sent once per net tick
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub tick: u32,
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub rope: Option<[f32; 2]>,        // end of the rope, None while stowed
+    pub second_rope: Option<[f32; 2]>, // double hook's extra anchor
+    pub tool: PlayerTool,
+    pub flashlight_direction: [f32; 2],
+}
+
+impl PlayerState {
+    pub fn from_player(player: &Player, tick: u32) -> Self {
+        let pair = |v: Vector2| [v.x, v.y];
+        Self {
+            tick,
+            position: pair(player.position),
+            velocity: pair(player.velocity),
+            rope: player.grappler.rope_end().map(pair),
+            second_rope: player.grappler.second_point.map(pair),
+            tool: player.current_tool,
+            flashlight_direction: pair(player.flashlight_direction),
+        }
+    }
+
+    pub fn position(&self) -> Vector2 {
+        Vector2::new(self.position[0], self.position[1])
+    }
+}
+
+// one json datagram, clients send State and Leave, the relay sends Peer and Left
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Packet {
+    State { version: u32, seed: u64, state: PlayerState },
+    Leave,
+    Peer { id: u32, state: PlayerState },
+    Left { id: u32 },
+}
+
+impl Packet {
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(io::Error::other)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Packet> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+// nothing arrived in time, or an earlier datagram bounced off a closed port
+pub fn is_quiet(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset
+    )
+}
+
+pub struct RemotePlayer {
+    pub state: PlayerState,
+    pub last_seen: Instant,
+}
+
+// game side of the relay, never blocks the frame
+pub struct NetClient {
+    socket: UdpSocket,
+    seed: u64,
+    tick: u32,
+    last_sent: Option<Instant>,
+    pub peers: HashMap<u32, RemotePlayer>,
+}
+
+impl NetClient {
+    pub fn connect(relay: impl ToSocketAddrs, seed: u64) -> io::Result<Self> {
+        let relay = relay
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "relay address did not resolve"))?;
+        let local = if relay.is_ipv4() {
+            SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+        } else {
+            SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(relay)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, seed, tick: 0, last_sent: None, peers: HashMap::new() })
+    }
+
+    // called every frame, only actually sends once per SEND_INTERVAL
+    pub fn send(&mut self, player: &Player) {
+        if self.last_sent.is_some_and(|sent| sent.elapsed() < SEND_INTERVAL) {
+            return;
+        }
+        self.last_sent = Some(Instant::now());
+        self.send_state(PlayerState::from_player(player, self.tick));
+    }
+
+    pub fn send_state(&mut self, state: PlayerState) {
+        self.tick = self.tick.wrapping_add(1);
+        let packet = Packet::State { version: PROTOCOL_VERSION, seed: self.seed, state };
+        match packet.encode() {
+            // a missing relay just means nobody shows up
+            Ok(bytes) => {
+                let _ = self.socket.send(&bytes);
+            }
+            Err(err) => eprintln!("could not encode state: {}", err),
+        }
+    }
+
+    // reads everything that arrived since the last frame
+    pub fn poll(&mut self) {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let size = match self.socket.recv(&mut buffer) {
+                Ok(size) => size,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if is_quiet(&err) => continue,
+                Err(err) => {
+                    eprintln!("relay connection: {}", err);
+                    break;
+                }
+            };
+            match Packet::decode(&buffer[..size]) {
+                Some(Packet::Peer { id, state }) => {
+                    // datagrams can arrive out of order, keep the newest
+                    let newer = self.peers.get(&id).is_none_or(|peer| state.tick > peer.state.tick);
+                    if newer {
+                        self.peers.insert(id, RemotePlayer { state, last_seen: Instant::now() });
+                    }
+                }
+                Some(Packet::Left { id }) => {
+                    self.peers.remove(&id);
+                }
+                _ => {}
+            }
+        }
+        // in case a Left got lost
+        self.peers.retain(|_, peer| peer.last_seen.elapsed() <= PEER_TIMEOUT);
+    }
+}
+
+// tell the relay right away instead of waiting for the timeout
+impl Drop for NetClient {
+    fn drop(&mut self) {
+        if let Ok(bytes) = Packet::Leave.encode() {
+            let _ = self.socket.send(&bytes);
+        }
+    }
+}
+
+// remote players show up as ghosts with their ropes
+pub fn draw_peers(d: &mut impl RaylibDraw, client: &NetClient) {
+    for (id, peer) in &client.peers {
+        let state = &peer.state;
+        let position = state.position();
+        for end in [state.rope, state.second_rope].into_iter().flatten() {
+            d.draw_line_v(position, Vector2::new(end[0], end[1]), ROPE_COLOR);
+        }
+        if state.tool == PlayerTool::Flashlight {
+            let direction = Vector2::new(state.flashlight_direction[0], state.flashlight_direction[1]);
+            d.draw_line_v(position, position + direction * PLAYER_SIZE * 2.0, ROPE_COLOR);
+        }
+        ghost::draw_ghost(d, position);
+        d.draw_text(
+            &format!("P{}", id),
+            (position.x - PLAYER_SIZE) as i32,
+            (position.y - PLAYER_SIZE * 2.0 - 10.0) as i32,
+            10,
+            Color::DARKGRAY,
+        );
+    }
+}
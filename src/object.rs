@@ -1,11 +1,39 @@
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
 
-const OBJECT_COLOR: Color = Color::RED;
+use crate::biome::Theme;
 
-#[derive(Copy, Clone)]
+// what touching an object does
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum ObjectKind {
+    #[default]
+    Solid,
+    Unhookable, // stands like solid but the grapple slips off
+    Hazard,     // touching it counts as a fall
+}
+
+impl ObjectKind {
+    pub const ALL: [ObjectKind; 3] = [ObjectKind::Solid, ObjectKind::Unhookable, ObjectKind::Hazard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObjectKind::Solid => "Solid",
+            ObjectKind::Unhookable => "Unhookable",
+            ObjectKind::Hazard => "Hazard",
+        }
+    }
+
+    pub fn next(&self) -> ObjectKind {
+        let index = ObjectKind::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        ObjectKind::ALL[(index + 1) % ObjectKind::ALL.len()]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
 pub struct Object {
     pub position: Vector2,
-    pub size: Vector2
+    pub size: Vector2,
+    pub kind: ObjectKind,
 }
 
 struct Vector2Int {
@@ -25,17 +53,36 @@ impl Vector2Int {
 
 impl Object {
     pub fn new(position: Vector2, size: Vector2) -> Self {
-        Self { position, size }
+        Self { position, size, kind: ObjectKind::Solid }
+    }
+
+    pub fn with_kind(self, kind: ObjectKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    pub fn hookable(&self) -> bool {
+        self.kind != ObjectKind::Unhookable
+    }
+
+    // circle against rect, no raylib call so it works headless
+    pub fn touches_circle(&self, center: Vector2, radius: f32) -> bool {
+        let closest = Vector2::new(
+            center.x.clamp(self.position.x, self.position.x + self.size.x),
+            center.y.clamp(self.position.y, self.position.y + self.size.y),
+        );
+        (center - closest).length_sqr() <= radius * radius
     }
 
     pub fn get_rect(&self) -> Rectangle {
         Rectangle::new(self.position.x, self.position.y, self.size.x, self.size.y)
     }
 
-    pub fn draw(&self, d: &mut impl RaylibDraw) {
+    pub fn draw(&self, d: &mut impl RaylibDraw, theme: &Theme) {
         let position_int = Vector2Int::from_vector2(self.position);
         let size_int = Vector2Int::from_vector2(self.size);
 
-        d.draw_rectangle(position_int.x, position_int.y, size_int.x, size_int.y, OBJECT_COLOR);
+        // coloured by the biome on screen
+        let color = theme.object_color(self.kind);
+        d.draw_rectangle(position_int.x, position_int.y, size_int.x, size_int.y, color);
     }
 }
@@ -0,0 +1,259 @@
+use std::f32::consts::PI;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use raylib::prelude::*;
+
+use crate::biome::mix_color;
+use crate::events::GameEvent;
+use crate::player::{PLAYER_SIZE, Player};
+
+pub const MAX_PARTICLES: usize = 1024;
+const LANDING_SPEED: f32 = 150.0; // softer landings don't kick up dust
+const WALL_SLIDE_RATE: f32 = 30.0; // dust per second while sliding
+
+// how one kind of particle moves and fades, copied into every particle it emits
+#[derive(Copy, Clone, Debug)]
+pub struct ParticleStyle {
+    pub life: f32,
+    pub speed: (f32, f32), // random between the two
+    pub spread: f32, // radians either side of the emit direction
+    pub gravity: f32,
+    pub drag: f32, // fraction of velocity lost per second
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub light: f32, // light radius at birth, 0 for none
+}
+
+pub const DUST: ParticleStyle = ParticleStyle {
+    life: 0.5,
+    speed: (30.0, 90.0),
+    spread: 1.2,
+    gravity: 60.0,
+    drag: 3.0,
+    start_color: Color::new(200, 190, 170, 200),
+    end_color: Color::new(200, 190, 170, 0),
+    start_size: 3.0,
+    end_size: 6.0,
+    light: 0.0,
+};
+
+pub const SPARKS: ParticleStyle = ParticleStyle {
+    life: 0.35,
+    speed: (120.0, 260.0),
+    spread: 0.9,
+    gravity: 500.0,
+    drag: 1.0,
+    start_color: Color::new(255, 240, 160, 255),
+    end_color: Color::new(255, 90, 20, 0),
+    start_size: 2.0,
+    end_size: 0.5,
+    light: 40.0,
+};
+
+pub const WALL_DUST: ParticleStyle = ParticleStyle {
+    life: 0.4,
+    speed: (10.0, 40.0),
+    spread: 0.6,
+    gravity: -20.0, // drifts up past a sliding player
+    drag: 2.0,
+    start_color: Color::new(180, 180, 180, 160),
+    end_color: Color::new(180, 180, 180, 0),
+    start_size: 2.0,
+    end_size: 4.0,
+    light: 0.0,
+};
+
+pub const RESPAWN: ParticleStyle = ParticleStyle {
+    life: 0.8,
+    speed: (40.0, 140.0),
+    spread: PI,
+    gravity: 0.0,
+    drag: 2.5,
+    start_color: Color::new(140, 200, 255, 255),
+    end_color: Color::new(255, 255, 255, 0),
+    start_size: 3.0,
+    end_size: 1.0,
+    light: 60.0,
+};
+
+#[derive(Copy, Clone, Debug)]
+pub struct Particle {
+    pub position: Vector2,
+    pub velocity: Vector2,
+    pub age: f32,
+    pub style: ParticleStyle,
+}
+
+impl Particle {
+    // 0 when born, 1 when it dies
+    pub fn progress(&self) -> f32 {
+        (self.age / self.style.life).clamp(0.0, 1.0)
+    }
+
+    pub fn color(&self) -> Color {
+        mix_color(self.style.start_color, self.style.end_color, self.progress())
+    }
+
+    pub fn size(&self) -> f32 {
+        self.style.start_size + (self.style.end_size - self.style.start_size) * self.progress()
+    }
+
+    pub fn light_radius(&self) -> f32 {
+        self.style.light * (1.0 - self.progress())
+    }
+}
+
+// live particles, never more than MAX_PARTICLES so the vec never grows
+pub struct ParticlePool {
+    particles: Vec<Particle>,
+    rng: ChaCha8Rng,
+}
+
+impl ParticlePool {
+    pub fn new() -> Self {
+        Self { particles: Vec::with_capacity(MAX_PARTICLES), rng: ChaCha8Rng::seed_from_u64(0) }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    // a full pool drops new particles rather than growing
+    pub fn emit(&mut self, position: Vector2, direction: Vector2, style: &ParticleStyle) {
+        if self.particles.len() >= MAX_PARTICLES {
+            return;
+        }
+        let angle = direction.y.atan2(direction.x) + self.rng.random_range(-style.spread..=style.spread);
+        let speed = self.rng.random_range(style.speed.0..=style.speed.1);
+        self.particles.push(Particle {
+            position,
+            velocity: Vector2::new(angle.cos(), angle.sin()) * speed,
+            age: 0.0,
+            style: *style,
+        });
+    }
+
+    pub fn burst(&mut self, position: Vector2, direction: Vector2, count: usize, style: &ParticleStyle) {
+        for _ in 0..count {
+            self.emit(position, direction, style);
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.age += delta_time;
+            particle.velocity.y += particle.style.gravity * delta_time;
+            particle.velocity *= (1.0 - particle.style.drag * delta_time).max(0.0);
+            particle.position += particle.velocity * delta_time;
+        }
+        // swap_remove keeps the capacity, nothing is allocated after the first bursts
+        let mut i = 0;
+        while i < self.particles.len() {
+            if self.particles[i].age >= self.particles[i].style.life {
+                self.particles.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw) {
+        for particle in self.particles() {
+            d.draw_circle_v(particle.position, particle.size(), particle.color());
+        }
+    }
+
+    // world positions and radii of the particles that glow
+    pub fn lights(&self) -> impl Iterator<Item = (Vector2, f32)> + '_ {
+        self.particles()
+            .iter()
+            .filter(|particle| particle.style.light > 0.0)
+            .map(|particle| (particle.position, particle.light_radius()))
+    }
+}
+
+impl Default for ParticlePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// keeps spawning while active, rate is particles per second
+pub struct Emitter {
+    pub position: Vector2,
+    pub direction: Vector2,
+    pub rate: f32,
+    pub style: ParticleStyle,
+    pub active: bool,
+    accumulator: f32,
+}
+
+impl Emitter {
+    pub fn new(rate: f32, style: ParticleStyle) -> Self {
+        Self { position: Vector2::zero(), direction: Vector2::new(0.0, -1.0), rate, style, active: false, accumulator: 0.0 }
+    }
+
+    pub fn update(&mut self, delta_time: f32, pool: &mut ParticlePool) {
+        if !self.active {
+            self.accumulator = 0.0;
+            return;
+        }
+        self.accumulator += self.rate * delta_time;
+        while self.accumulator >= 1.0 {
+            self.accumulator -= 1.0;
+            pool.emit(self.position, self.direction, &self.style);
+        }
+    }
+}
+
+// one-off bursts for what happened this frame
+pub fn handle_event(event: &GameEvent, pool: &mut ParticlePool) {
+    match *event {
+        // the harder the landing the more dust
+        GameEvent::Landed { position, speed } if speed > LANDING_SPEED => {
+            let feet = position + Vector2::new(0.0, PLAYER_SIZE);
+            let count = (speed / 40.0) as usize;
+            pool.burst(feet, Vector2::new(-1.0, -0.3), count / 2, &DUST);
+            pool.burst(feet, Vector2::new(1.0, -0.3), count / 2, &DUST);
+        }
+        // sparks fly back along the rope
+        GameEvent::HookAttached { point, origin } => pool.burst(point, origin - point, 12, &SPARKS),
+        GameEvent::Respawned { position } => pool.burst(position, Vector2::new(0.0, -1.0), 30, &RESPAWN),
+        GameEvent::Caught { position } => pool.burst(position, Vector2::new(0.0, -1.0), 20, &WALL_DUST),
+        GameEvent::Collected { position, .. } => pool.burst(position, Vector2::new(0.0, -1.0), 10, &SPARKS),
+        _ => {}
+    }
+}
+
+// wall dust runs for as long as the slide, so it follows the player instead of an event
+pub struct PlayerEffects {
+    wall_dust: Emitter,
+}
+
+impl PlayerEffects {
+    pub fn new() -> Self {
+        Self { wall_dust: Emitter::new(WALL_SLIDE_RATE, WALL_DUST) }
+    }
+
+    pub fn update(&mut self, delta_time: f32, player: &Player, pool: &mut ParticlePool) {
+        // dust scraped off the wall face
+        match player.wall {
+            Some(wall) if !player.grounded && player.velocity.y > 0.0 => {
+                self.wall_dust.active = true;
+                self.wall_dust.position = Vector2::new(wall.x, player.position.y + PLAYER_SIZE / 2.0);
+                self.wall_dust.direction = Vector2::new(wall.side.away(), -0.5);
+            }
+            _ => self.wall_dust.active = false,
+        }
+        self.wall_dust.update(delta_time, pool);
+    }
+}
+
+impl Default for PlayerEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
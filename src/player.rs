@@ -1,25 +1,114 @@
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::object::Object;
-use crate::grappler::Grappler;
+use crate::grappler::{Grappler, HookType};
+use crate::input::InputState;
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PlayerTool {
     Grapple,
     Flashlight,
 }
 
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum WallSide {
+    Left,
+    Right,
+}
+
+impl WallSide {
+    // horizontal direction pointing away from the wall
+    pub fn away(&self) -> f32 {
+        match self {
+            WallSide::Left => 1.0,
+            WallSide::Right => -1.0,
+        }
+    }
+}
+
+// which side the wall is on and where its face is, so the same wall can be recognised
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct WallContact {
+    pub side: WallSide,
+    pub x: f32,
+}
+
 pub const PLAYER_SIZE: f32 = 10.0;
-const GRAVITY: f32 = 500.0;
+pub const FALL_Y: f32 = 1000.0; // below this you have fallen off the world
+const BUNGEE_STIFFNESS: f32 = 12.0; // spring force per unit of stretch
+pub const MAX_SWING_SPEED: f32 = 800.0; // reeling in never speeds a swing past this
+const BATTERY_DRAIN: f32 = 1.0 / 90.0; // a full battery lasts a minute and a half
+
+// movement tuning, speeds in units per second and times in seconds
+#[derive(Clone, Copy)]
+pub struct PhysicsConfig {
+    pub gravity: f32,
+    pub move_speed: f32,
+    pub ground_acceleration: f32,
+    pub ground_friction: f32,
+    pub air_acceleration: f32, // air steering never cancels swing momentum
+    pub jump_speed: f32,
+    pub jump_cut: f32, // upward speed kept when jump is let go early
+    pub jump_buffer: f32, // how early a jump press still counts before landing
+    pub coyote_time: f32, // how late you can still jump after leaving a ledge
+    pub fast_fall_acceleration: f32,
+    pub double_jump_speed: f32,
+    pub dash_speed: f32,
+    pub dash_time: f32,
+    pub wall_slide_speed: f32, // fastest fall while pressed against a wall
+    pub wall_jump_speed: f32,
+    pub wall_jump_kick: f32, // horizontal push away from the wall
+    pub max_same_wall_jumps: u32, // jumps off one wall before you need another or the ground
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 500.0,
+            move_speed: 150.0,
+            ground_acceleration: 1200.0,
+            ground_friction: 1200.0,
+            air_acceleration: 600.0,
+            jump_speed: 300.0,
+            jump_cut: 0.5,
+            jump_buffer: 0.1,
+            coyote_time: 0.1,
+            fast_fall_acceleration: 200.0,
+            double_jump_speed: 260.0,
+            dash_speed: 350.0,
+            dash_time: 0.15,
+            wall_slide_speed: 50.0,
+            wall_jump_speed: 280.0,
+            wall_jump_kick: 220.0,
+            max_same_wall_jumps: 2,
+        }
+    }
+}
 
 pub struct Player {
     pub position: Vector2,
     pub velocity: Vector2,
     pub grounded: bool,
-    pub wall_sliding: bool,
+    pub wall: Option<WallContact>,
     pub grappler: Grappler,
     pub current_tool: PlayerTool,
     pub flashlight_direction: Vector2,
+    pub color: Color,
+    pub physics: PhysicsConfig,
+    pub can_double_jump: bool, // unlocked abilities
+    pub can_dash: bool,
+    pub facing: f32, // -1 left, 1 right
+    pub jumped: bool, // a jump started this tick
+    pub battery: f32, // flashlight charge, 0 .. 1
+    jump_buffer_timer: f32,
+    coyote_timer: f32,
+    jump_rising: bool, // a jump that can still be cut short
+    double_jump_used: bool,
+    dash_used: bool,
+    pub dash_timer: f32,
+    last_wall_jump: Option<WallContact>,
+    same_wall_jumps: u32,
 }
 
 pub struct UpdateState {
@@ -32,49 +121,88 @@ impl Player {
             position,
             velocity: Vector2::zero(),
             grounded: true,
-            wall_sliding: false,
+            wall: None,
             grappler: Grappler::new(position),
             current_tool: PlayerTool::Grapple,
             flashlight_direction: Vector2::zero(),
+            color: Color::BLUE,
+            physics: PhysicsConfig::default(),
+            can_double_jump: false,
+            can_dash: false,
+            facing: 1.0,
+            jumped: false,
+            battery: 1.0,
+            jump_buffer_timer: 0.0,
+            coyote_timer: 0.0,
+            jump_rising: false,
+            double_jump_used: false,
+            dash_used: false,
+            dash_timer: 0.0,
+            last_wall_jump: None,
+            same_wall_jumps: 0,
         }
     }
 
+    // an empty battery leaves only the glow around the player
+    pub fn flashlight_on(&self) -> bool {
+        self.current_tool == PlayerTool::Flashlight && self.battery > 0.0
+    }
+
     pub fn update(&mut self, delta_time: f32, is_respawning: bool) -> UpdateState {
         let mut update_state = UpdateState { fell: false };
         
         self.grappler.position = self.position;
         self.grounded = false;
-        self.wall_sliding = false;
+        self.wall = None;
         
         if !is_respawning {
             match self.grappler.state {
                 crate::grappler::GrapplerState::Pulling => {
-                    self.velocity.y += GRAVITY * delta_time;
+                    self.velocity.y += self.physics.gravity * delta_time;
                     self.position += self.velocity * delta_time;
 
-                    const REEL_IN_SPEED: f32 = 600.0;
-                    self.grappler.length = (self.grappler.length - REEL_IN_SPEED * delta_time).max(0.0);
+                    let previous_length = self.grappler.length;
+                    let reel_speed = self.grappler.stats().reel_speed;
+                    self.grappler.length = (self.grappler.length - reel_speed * delta_time).max(0.0);
 
-                    self.apply_rope_constraint();
+                    if self.grappler.hook == HookType::Bungee {
+                        // the shrinking rest length slings you, then the hook lets go
+                        self.apply_bungee_force(delta_time);
+                        if self.grappler.length == 0.0 {
+                            self.release_grapple(0.2);
+                        }
+                    } else {
+                        self.apply_rope_constraint(previous_length);
+                    }
 
-                    if self.grappler.length == 0.0 {
+                    if self.grappler.length == 0.0 && self.grappler.hook != HookType::Bungee {
                         self.position = self.grappler.grapple_point;
-                        self.velocity = Vector2::zero();
-                        self.grappler.state = crate::grappler::GrapplerState::Idle;
+                        self.release_grapple(0.0);
                     }
 
                     self.velocity *= 0.99;
                 }
                 crate::grappler::GrapplerState::Grappled => {
-                    self.velocity.y += GRAVITY * delta_time;
+                    self.velocity.y += self.physics.gravity * delta_time;
                     self.position += self.velocity * delta_time;
 
-                    self.apply_rope_constraint();
+                    let previous_length = self.grappler.length;
+                    self.grappler.apply_reel(delta_time);
+
+                    if self.grappler.hook == HookType::Bungee {
+                        self.apply_bungee_force(delta_time);
+                    } else {
+                        self.apply_rope_constraint(previous_length);
+                    }
+                    self.apply_second_rope_constraint();
 
                     self.velocity *= 0.99;
                 }
                 _ => {
-                    self.velocity.y += GRAVITY * delta_time;
+                    // a dash holds its height
+                    if self.dash_timer <= 0.0 {
+                        self.velocity.y += self.physics.gravity * delta_time;
+                    }
                     self.position += self.velocity * delta_time;
                 }
             }
@@ -100,9 +228,7 @@ impl Player {
             let obj_rect = enumerated_object.get_rect();
             if pred_right > obj_rect.x && pred_left < obj_rect.x + obj_rect.width &&
                pred_bottom > obj_rect.y && pred_top < obj_rect.y + obj_rect.height {
-                self.grappler.state = crate::grappler::GrapplerState::Idle;
-                self.grappler.length = 0.0;
-                self.grappler.release_cooldown = 0.1; // brief cooldown
+                self.grappler.release(0.1); // brief cooldown
             }
         }
 
@@ -112,7 +238,8 @@ impl Player {
         } else {
             10.0
         };
-        const SLIDE_SPEED: f32 = 50.0;
+        const CEILING_BOUNCE_SPEED: f32 = 50.0;
+        let wall_slide_speed = self.physics.wall_slide_speed;
         
         let expanded_width = enumerated_object.size.x + (horizontal_expansion * 2.0);
         let object_left = enumerated_object.position.x - horizontal_expansion;
@@ -162,13 +289,13 @@ impl Player {
                     if self.velocity.x > 0.0 {
                         self.velocity.x = 0.0;
                     }
-                    self.wall_sliding = true;
+                    self.wall = Some(WallContact { side: WallSide::Right, x: orig_object_left });
                 } else if min_overlap == overlap_right {
                     self.position.x = orig_object_right + PLAYER_SIZE;
                     if self.velocity.x < 0.0 {
                         self.velocity.x = 0.0;
                     }
-                    self.wall_sliding = true;
+                    self.wall = Some(WallContact { side: WallSide::Left, x: orig_object_right });
                 }
                 return false;
             }
@@ -180,38 +307,43 @@ impl Player {
                 return true;
             } else if min_overlap == overlap_bottom && self.velocity.y < 0.0 {
                 self.position.y = object_bottom + PLAYER_SIZE;
-                self.velocity.y = SLIDE_SPEED;
-                self.wall_sliding = true;
+                self.velocity.y = CEILING_BOUNCE_SPEED;
             } else if min_overlap == overlap_left && self.velocity.x > 0.0 {
+                // rising keeps its speed, falling is capped to a slide
                 self.position.x = orig_object_left - PLAYER_SIZE;
                 self.velocity.x = 0.0;
-                self.velocity.y = SLIDE_SPEED;
-                self.wall_sliding = true;
+                self.velocity.y = self.velocity.y.min(wall_slide_speed);
+                self.wall = Some(WallContact { side: WallSide::Right, x: orig_object_left });
             } else if min_overlap == overlap_right && self.velocity.x < 0.0 {
                 self.position.x = orig_object_right + PLAYER_SIZE;
                 self.velocity.x = 0.0;
-                self.velocity.y = SLIDE_SPEED;
-                self.wall_sliding = true;
+                self.velocity.y = self.velocity.y.min(wall_slide_speed);
+                self.wall = Some(WallContact { side: WallSide::Left, x: orig_object_right });
             }
         }
         
         false
     }
 
-    pub fn input(&mut self, delta_time: f32, rl: &mut RaylibHandle, objects: &[Object], mouse_pos: Vector2) {
-        let prev_grapple_state = self.grappler.state;
+    pub fn input(&mut self, delta_time: f32, input: &InputState, objects: &[Object]) {
+        let mouse_pos = input.aim_target;
+        self.jumped = false;
         
         // Tool switching
-        if rl.is_key_pressed(KeyboardKey::KEY_ONE) {
+        if input.select_grapple {
             self.current_tool = PlayerTool::Grapple;
-        } else if rl.is_key_pressed(KeyboardKey::KEY_TWO) {
+        } else if input.select_flashlight {
             self.current_tool = PlayerTool::Flashlight;
             // Release grapple on tool switch
             self.grappler.state = crate::grappler::GrapplerState::Idle;
         }
+        if input.cycle_hook {
+            self.grappler.cycle_hook();
+        }
         
         // Flashlight direction toward mouse
         if self.current_tool == PlayerTool::Flashlight {
+            self.battery = (self.battery - BATTERY_DRAIN * delta_time).max(0.0);
             let direction = mouse_pos - self.position;
             self.flashlight_direction = if direction.length_sqr() > 0.0 {
                 direction.normalized()
@@ -219,67 +351,164 @@ impl Player {
                 Vector2::new(1.0, 0.0)
             };
         }
+
+        // Aim assist and hit prediction
+        if self.current_tool == PlayerTool::Grapple {
+            self.grappler.update_aim(self.position, mouse_pos, objects);
+        } else {
+            self.grappler.aim_target = None;
+            self.grappler.predicted_hit = None;
+        }
         
         // Grapple fire/release
-        if self.current_tool == PlayerTool::Grapple && self.grappler.release_cooldown <= 0.0 {
-            if rl.is_key_pressed(KeyboardKey::KEY_E) || rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
-                match self.grappler.state {
-                    crate::grappler::GrapplerState::Idle => self.grappler.fire(self.position, mouse_pos),
-                    crate::grappler::GrapplerState::Grappled => self.grappler.state = crate::grappler::GrapplerState::Pulling,
-                    crate::grappler::GrapplerState::Pulling => {
-                        self.grappler.state = crate::grappler::GrapplerState::Idle;
-                        self.grappler.release_cooldown = 0.2; // cooldown on release
-                    },
-                    _ => {},
-                }
+        // Held reel input is applied while swinging
+        self.grappler.reel = input.reel;
+
+        if self.current_tool == PlayerTool::Grapple && self.grappler.release_cooldown <= 0.0 && input.fire {
+            match self.grappler.state {
+                crate::grappler::GrapplerState::Idle => self.grappler.fire_assisted(self.position, mouse_pos),
+                crate::grappler::GrapplerState::Grappled => self.grappler.fire_second_or_reel(mouse_pos, objects),
+                crate::grappler::GrapplerState::Pulling => self.release_grapple(0.2), // cooldown on release
+                _ => {},
             }
         }
 
         // Grappler update
         self.grappler.update(delta_time, objects);
+        if self.grappler.state != crate::grappler::GrapplerState::Grappled {
+            self.grappler.second_point = None;
+        }
         if self.grappler.release_cooldown > 0.0 {
             self.grappler.release_cooldown -= delta_time;
         }
 
-        // Reset velocity on grapple release
-        if prev_grapple_state != crate::grappler::GrapplerState::Idle && self.grappler.state == crate::grappler::GrapplerState::Idle {
-            self.velocity = Vector2::zero();
+        // Jumping lets go of the rope mid-swing
+        let attached = self.grappler.state == crate::grappler::GrapplerState::Grappled
+            || self.grappler.state == crate::grappler::GrapplerState::Pulling;
+        if input.jump_pressed && attached {
+            self.release_grapple(0.2);
+        } else if !attached {
+            self.jump(delta_time, input);
         }
-        
-        // Jumping
-        if rl.is_key_down(KeyboardKey::KEY_SPACE) ||
-           rl.is_key_down(KeyboardKey::KEY_W) ||
-           rl.is_key_down(KeyboardKey::KEY_UP) {
-            
-            if self.grounded {
-                self.velocity.y = -300.0; // normal jump
-            } else if self.wall_sliding {
-                self.velocity.y = -300.0; // wall climb boost
+
+        self.run(delta_time, input);
+
+        // Dashing
+        if self.dash_timer > 0.0 {
+            self.dash_timer -= delta_time;
+            // the dash ends back at running speed
+            if self.dash_timer <= 0.0 {
+                let move_speed = self.physics.move_speed;
+                self.velocity.x = self.velocity.x.clamp(-move_speed, move_speed);
             }
         }
+        if input.dash && self.can_dash && !self.dash_used && !self.grounded && !attached {
+            self.dash_used = true;
+            self.dash_timer = self.physics.dash_time;
+            self.velocity = Vector2::new(self.facing * self.physics.dash_speed, 0.0);
+        }
+
+        // Fast fall
+        if input.fast_fall && !self.grounded {
+            self.velocity.y += self.physics.fast_fall_acceleration * delta_time;
+        }
+    }
+
+    fn jump(&mut self, delta_time: f32, input: &InputState) {
+        let physics = self.physics;
 
-        if rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(KeyboardKey::KEY_A) {
-            self.velocity.x = -150.0;
-        } else if rl.is_key_down(KeyboardKey::KEY_RIGHT) || rl.is_key_down(KeyboardKey::KEY_D) {
-            self.velocity.x = 150.0;
+        // landing gives the air abilities back
+        if self.grounded {
+            self.coyote_timer = physics.coyote_time;
+            self.double_jump_used = false;
+            self.dash_used = false;
+            self.last_wall_jump = None;
+            self.same_wall_jumps = 0;
         } else {
-            self.velocity.x *= 0.8; 
+            self.coyote_timer -= delta_time;
         }
-        
-        // Fast fall
-        if (rl.is_key_down(KeyboardKey::KEY_S) ||
-            rl.is_key_down(KeyboardKey::KEY_DOWN)) &&
-            !self.grounded {
-            self.velocity.y += 200.0 * delta_time;
+
+        if input.jump_pressed {
+            self.jump_buffer_timer = physics.jump_buffer;
+        } else {
+            self.jump_buffer_timer -= delta_time;
+        }
+
+        if self.jump_buffer_timer > 0.0 {
+            if self.grounded || self.coyote_timer > 0.0 {
+                self.velocity.y = -physics.jump_speed; // normal jump
+            } else if let Some(wall) = self.wall
+                && self.can_wall_jump(wall)
+            {
+                self.wall_jump(wall);
+            } else if self.can_double_jump && !self.double_jump_used && input.jump_pressed {
+                self.double_jump_used = true;
+                self.velocity.y = -physics.double_jump_speed;
+            } else {
+                return;
+            }
+            self.jump_buffer_timer = 0.0;
+            self.coyote_timer = 0.0;
+            self.jump_rising = true;
+            self.jumped = true;
+        }
+
+        // letting go early cuts the jump short
+        if self.velocity.y >= 0.0 {
+            self.jump_rising = false;
+        } else if self.jump_rising && !input.jump {
+            self.velocity.y *= physics.jump_cut;
+            self.jump_rising = false;
+        }
+    }
+
+    // the same wall only takes so many jumps in a row, so walls can't be climbed forever
+    fn can_wall_jump(&self, wall: WallContact) -> bool {
+        self.last_wall_jump != Some(wall) || self.same_wall_jumps < self.physics.max_same_wall_jumps
+    }
+
+    fn wall_jump(&mut self, wall: WallContact) {
+        if self.last_wall_jump == Some(wall) {
+            self.same_wall_jumps += 1;
+        } else {
+            self.last_wall_jump = Some(wall);
+            self.same_wall_jumps = 1;
+        }
+        self.facing = wall.side.away();
+        self.velocity = Vector2::new(
+            wall.side.away() * self.physics.wall_jump_kick,
+            -self.physics.wall_jump_speed,
+        );
+    }
+
+    fn run(&mut self, delta_time: f32, input: &InputState) {
+        let physics = self.physics;
+        if input.move_x != 0.0 {
+            self.facing = input.move_x.signum();
+        }
+
+        let target = physics.move_speed * input.move_x;
+        if self.grounded {
+            let rate = if input.move_x != 0.0 {
+                physics.ground_acceleration
+            } else {
+                physics.ground_friction
+            };
+            self.velocity.x = approach(self.velocity.x, target, rate * delta_time);
+        } else if input.move_x != 0.0 {
+            // only push while under move speed in that direction
+            if (target > 0.0 && self.velocity.x < target) || (target < 0.0 && self.velocity.x > target) {
+                self.velocity.x = approach(self.velocity.x, target, physics.air_acceleration * delta_time);
+            }
         }
     }
 
     pub fn has_fallen(&self) -> bool {
-        self.position.y > 1000.0
+        self.position.y > FALL_Y
     }
     
     pub fn draw(&self, d: &mut impl RaylibDraw) {
-        d.draw_circle(self.position.x as i32, self.position.y as i32, PLAYER_SIZE, Color::BLUE);
+        d.draw_circle(self.position.x as i32, self.position.y as i32, PLAYER_SIZE, self.color);
     }
     
     pub fn draw_grappler(&self, d: &mut impl RaylibDraw, mouse_position: Vector2) {
@@ -298,7 +527,18 @@ impl Player {
     }
 
 
-    fn apply_rope_constraint(&mut self) {
+    // letting go keeps the swing's velocity and adds a small boost along it
+    pub fn release_grapple(&mut self, cooldown: f32) {
+        let boost = self.grappler.stats().release_boost;
+        self.grappler.release(cooldown);
+        if self.velocity.length_sqr() > 0.0 {
+            self.velocity += self.velocity.normalized() * boost;
+        }
+    }
+
+    // a rope that changed length since last frame keeps angular momentum,
+    // so reeling in speeds the swing up and letting out slows it down
+    fn apply_rope_constraint(&mut self, previous_length: f32) {
         let rope_vec = self.position - self.grappler.grapple_point;
         let dist = rope_vec.length();
         if dist > 0.0 {
@@ -309,6 +549,51 @@ impl Player {
 
             let radial_speed = self.velocity.dot(rope_dir);
             self.velocity -= rope_dir * radial_speed;
+
+            // reeling keeps angular momentum, very short ropes would multiply the speed without bound
+            let speed = self.velocity.length();
+            if self.grappler.length >= crate::grappler::MIN_ROPE_LENGTH && speed > 0.0 {
+                let scaled = speed * previous_length / self.grappler.length;
+                // something already faster than the cap isn't sped up, only kept
+                self.velocity *= scaled.min(MAX_SWING_SPEED.max(speed)) / speed;
+            }
         }
     }
+
+    // the second anchor only pulls when its rope is taut
+    fn apply_second_rope_constraint(&mut self) {
+        let Some(anchor) = self.grappler.second_point else {
+            return;
+        };
+        let rope_vec = self.position - anchor;
+        let dist = rope_vec.length();
+        if dist > self.grappler.second_length {
+            let rope_dir = rope_vec / dist;
+            self.position = anchor + rope_dir * self.grappler.second_length;
+
+            let radial_speed = self.velocity.dot(rope_dir);
+            if radial_speed > 0.0 {
+                self.velocity -= rope_dir * radial_speed;
+            }
+        }
+    }
+
+    // elastic rope, only pulls back once stretched past its length
+    fn apply_bungee_force(&mut self, delta_time: f32) {
+        let rope_vec = self.grappler.grapple_point - self.position;
+        let dist = rope_vec.length();
+        let stretch = dist - self.grappler.length;
+        if dist > 0.0 && stretch > 0.0 {
+            self.velocity += rope_vec / dist * stretch * BUNGEE_STIFFNESS * delta_time;
+        }
+    }
+}
+
+// moves value toward target by at most step
+fn approach(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
 }
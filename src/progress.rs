@@ -0,0 +1,77 @@
+use raylib::prelude::*;
+
+use crate::events::{EventQueue, GameEvent};
+use crate::player::Player;
+
+pub const START_POSITION: Vector2 = Vector2::new(100.0, 100.0);
+pub const CHECKPOINT_RADIUS: f32 = 20.0; // how close you need to get to a placed checkpoint
+pub const RECORD_STEP: f32 = 100.0; // a new best height is only announced once per this much climbing
+
+// height is measured upward, the world's y axis points down
+pub fn height_of(position: Vector2) -> f32 {
+    -position.y
+}
+
+pub struct Progress {
+    pub best_height: f32,
+    pub start: Vector2,
+    pub checkpoint: Option<Vector2>,
+    announced: f32, // best height at the last NewBestHeight event
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::starting_at(START_POSITION)
+    }
+
+    // hand-authored levels bring their own spawn
+    pub fn starting_at(start: Vector2) -> Self {
+        Self {
+            best_height: height_of(start),
+            start,
+            checkpoint: None,
+            announced: f32::MIN,
+        }
+    }
+
+    pub fn checkpoint_height(&self) -> f32 {
+        height_of(self.spawn_point())
+    }
+
+    pub fn spawn_point(&self) -> Vector2 {
+        self.checkpoint.unwrap_or(self.start)
+    }
+
+    pub fn update(&mut self, player: &Player, events: &mut EventQueue) {
+        let height = height_of(player.position);
+        if height <= self.best_height {
+            return;
+        }
+        self.best_height = height;
+        // once when the record is first beaten, then every step, not every frame of the climb
+        if height >= self.announced + RECORD_STEP {
+            self.announced = height;
+            events.push(GameEvent::NewBestHeight { height });
+        }
+    }
+
+    // placed checkpoints count as soon as you pass through them
+    pub fn touch_checkpoints(&mut self, position: Vector2, checkpoints: &[Vector2], events: &mut EventQueue) {
+        for checkpoint in checkpoints {
+            if position.distance_to(*checkpoint) <= CHECKPOINT_RADIUS && self.checkpoint != Some(*checkpoint) {
+                self.reach(*checkpoint, events);
+            }
+        }
+    }
+
+    fn reach(&mut self, checkpoint: Vector2, events: &mut EventQueue) {
+        self.checkpoint = Some(checkpoint);
+        events.push(GameEvent::CheckpointReached { position: checkpoint });
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
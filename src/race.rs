@@ -0,0 +1,380 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use crate::background::{self, generate_tile_layer, tile_layers_at_y};
+use darkswing::audio::Audio;
+use darkswing::biome;
+use darkswing::collectibles::Collectibles;
+use darkswing::events::{EventQueue, GameEvent};
+use darkswing::input::{self, InputReader};
+use darkswing::layergen::{generate_layer, layers_at_y, starting_objects};
+use darkswing::light;
+use darkswing::object::Object;
+use darkswing::particles::{self, ParticlePool, PlayerEffects};
+use darkswing::player::{PLAYER_SIZE, Player};
+use darkswing::progress::{Progress, START_POSITION, height_of};
+use darkswing::race_state::{RaceAction, RaceState};
+use darkswing::settings::Settings;
+use darkswing::sim;
+use darkswing::speedrun::format_time;
+use darkswing::sprites::{self, Animator, Atlas};
+use darkswing::with_drawing;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 4;
+pub const RACE_HEIGHT: f32 = 3000.0; // first one up wins
+const RESPAWN_DELAY: f32 = 1.5;
+const PLAYER_COLORS: [Color; MAX_PLAYERS] = [Color::BLUE, Color::RED, Color::DARKGREEN, Color::ORANGE];
+
+// one split-screen player with its own camera and device
+struct Racer {
+    player: Player,
+    input: InputReader,
+    progress: Progress,
+    camera: Camera2D,
+    animator: Animator,
+    effects: PlayerEffects,
+    respawn_timer: Option<f32>,
+    finish_time: Option<f32>,
+}
+
+// render targets for one racer's part of the window
+struct Viewport {
+    world: RenderTexture2D,
+    darkness: RenderTexture2D,
+}
+
+impl Viewport {
+    fn new(rl: &mut RaylibHandle, thread: &RaylibThread, rect: Rectangle) -> Option<Self> {
+        let (width, height) = (rect.width as u32, rect.height as u32);
+        let world = rl.load_render_texture(thread, width, height);
+        let darkness = rl.load_render_texture(thread, width, height);
+        match (world, darkness) {
+            (Ok(world), Ok(darkness)) => Some(Self { world, darkness }),
+            (Err(err), _) | (_, Err(err)) => {
+                eprintln!("could not create a {}x{} race view: {}", width, height, err);
+                None
+            }
+        }
+    }
+
+    fn fits(&self, rect: Rectangle) -> bool {
+        self.world.texture.width == rect.width as i32 && self.world.texture.height == rect.height as i32
+    }
+}
+
+// player 1 uses keyboard and mouse, everyone else gets the next gamepad
+fn reader_for(index: usize) -> InputReader {
+    if index == 0 {
+        InputReader::keyboard_only()
+    } else {
+        InputReader::gamepad_only(index as i32 - 1)
+    }
+}
+
+fn new_player(index: usize) -> Player {
+    // spread out a little so nobody spawns inside someone else
+    let mut player = Player::new(START_POSITION + Vector2::new(index as f32 * 30.0, 0.0));
+    player.color = PLAYER_COLORS[index];
+    player
+}
+
+// side by side for two, a 2x2 grid for three or four
+fn viewports(count: usize, window: Vector2) -> Vec<Rectangle> {
+    if count <= 2 {
+        let width = window.x / count as f32;
+        return (0..count)
+            .map(|i| Rectangle::new(i as f32 * width, 0.0, width, window.y))
+            .collect();
+    }
+    let (width, height) = (window.x / 2.0, window.y / 2.0);
+    (0..count)
+        .map(|i| Rectangle::new((i % 2) as f32 * width, (i / 2) as f32 * height, width, height))
+        .collect()
+}
+
+// finished racers by time, then everyone else by height
+fn standings(racers: &[Racer]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..racers.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&racers[a], &racers[b]);
+        match (a.finish_time, b.finish_time) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => b.progress.best_height.total_cmp(&a.progress.best_height),
+        }
+    });
+    order
+}
+
+pub fn run(
+    rl: &mut RaylibHandle,
+    thread: &mut RaylibThread,
+    settings: &Settings,
+    seed: u64,
+    count: usize,
+    audio_device: Option<&RaylibAudio>,
+) {
+    let count = count.clamp(MIN_PLAYERS, MAX_PLAYERS);
+    let light_tex = light::create_light_tex(800, 300.0, rl, thread);
+    let beam_tex = light::create_flashlight_beam_tex(200, 400, rl, thread);
+    let atlas = Atlas::load(rl, thread, sprites::ATLAS_PATH);
+    let mut particles = ParticlePool::new();
+    let mut audio = Audio::new(audio_device, settings);
+    let mut events = EventQueue::new();
+
+    let objects = starting_objects();
+    let mut layermap: HashMap<i32, Vec<Object>> = HashMap::new();
+    let mut tilemap: HashMap<i32, Vec<background::Tile>> = HashMap::new();
+    let mut collectibles = Collectibles::new();
+
+    let window = Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+    let mut racers: Vec<Racer> = (0..count)
+        .map(|i| Racer {
+            player: new_player(i),
+            input: reader_for(i),
+            progress: Progress::new(),
+            camera: Camera2D { zoom: 1.0, ..Default::default() },
+            animator: Animator::new(),
+            effects: PlayerEffects::new(),
+            respawn_timer: None,
+            finish_time: None,
+        })
+        .collect();
+    let views: Option<Vec<Viewport>> = viewports(count, window)
+        .into_iter()
+        .map(|rect| Viewport::new(rl, thread, rect))
+        .collect();
+    let Some(mut views) = views else {
+        return;
+    };
+
+    let mut state = RaceState::new();
+    let mut elapsed = 0.0;
+
+    while !rl.window_should_close() {
+        let delta_time = rl.get_frame_time();
+        let window = Vector2::new(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        let rects = viewports(count, window);
+
+        match state.update(delta_time, input::menu_requested(rl), input::menu_input(rl)) {
+            RaceAction::Continue => {}
+            RaceAction::Restart => {
+                for (i, racer) in racers.iter_mut().enumerate() {
+                    racer.player = new_player(i);
+                    racer.progress = Progress::new();
+                    racer.respawn_timer = None;
+                    racer.finish_time = None;
+                }
+                collectibles = Collectibles::new();
+                layermap.clear();
+                elapsed = 0.0;
+            }
+            RaceAction::Leave => return,
+        }
+
+        // world around every racer
+        let mut layers: Vec<i32> = racers.iter().flat_map(|r| layers_at_y(r.player.position.y)).collect();
+        layers.sort();
+        layers.dedup();
+        layermap.retain(|layer, _| layers.contains(layer));
+        collectibles.layers.retain(|layer, _| layers.contains(layer));
+        for layer in layers {
+            layermap.entry(layer).or_insert_with(|| {
+                let layer_objects = generate_layer(seed, layer);
+                collectibles.load_layer(seed, layer, &layer_objects);
+                layer_objects
+            });
+        }
+        let mut tile_layers: Vec<i32> = racers.iter().flat_map(|r| tile_layers_at_y(r.player.position.y)).collect();
+        tile_layers.sort();
+        tile_layers.dedup();
+        tilemap.retain(|layer, _| tile_layers.contains(layer));
+        for layer in tile_layers {
+            tilemap.entry(layer).or_insert_with(|| generate_tile_layer(seed, layer));
+        }
+
+        let mut all_objects = objects.clone();
+        for layer_objects in layermap.values() {
+            all_objects.extend(layer_objects.iter().cloned());
+        }
+
+        if state == RaceState::Racing {
+            elapsed += delta_time;
+            let mouse = rl.get_mouse_position();
+            for (racer, rect) in racers.iter_mut().zip(&rects) {
+                // mouse aim only makes sense inside your own viewport
+                let local_mouse = mouse - Vector2::new(rect.x, rect.y);
+                let mouse_world = rl.get_screen_to_world2D(local_mouse, racer.camera);
+                let input = racer.input.poll(rl, &settings.keybindings, mouse_world, racer.player.position);
+
+                racer.player.grappler.aim_assist = settings.aim_assist;
+                let respawning = racer.respawn_timer.is_some();
+                let update_state =
+                    sim::step(&mut racer.player, delta_time, &input, &all_objects, respawning, &mut events);
+                racer.progress.update(&racer.player, &mut events);
+                // first one there takes it, shards aren't kept from races
+                if !respawning {
+                    for kind in collectibles.pick_up(racer.player.position, &mut events) {
+                        kind.apply(&mut racer.player);
+                    }
+                }
+                racer.animator.update(delta_time, &racer.player);
+                racer.effects.update(delta_time, &racer.player, &mut particles);
+
+                if update_state.fell && !respawning {
+                    racer.respawn_timer = Some(0.0);
+                }
+                if let Some(timer) = racer.respawn_timer {
+                    if timer >= RESPAWN_DELAY {
+                        racer.player.position = racer.progress.spawn_point();
+                        racer.player.velocity = Vector2::zero();
+                        racer.player.battery = 1.0;
+                        events.push(GameEvent::Respawned { position: racer.player.position });
+                        racer.respawn_timer = None;
+                    } else {
+                        racer.respawn_timer = Some(timer + delta_time);
+                    }
+                }
+
+                if racer.finish_time.is_none() && height_of(racer.player.position) >= RACE_HEIGHT {
+                    racer.finish_time = Some(elapsed);
+                }
+            }
+            particles.update(delta_time);
+            if racers.iter().any(|racer| racer.finish_time.is_some()) {
+                state = RaceState::Results;
+            }
+        }
+
+        // one pair of ears between all the racers
+        let listener = racers.iter().map(|racer| racer.player.position).fold(Vector2::zero(), |sum, p| sum + p)
+            / racers.len() as f32;
+        for event in events.iter() {
+            particles::handle_event(event, &mut particles);
+            audio.mixer.handle_event(event);
+        }
+        events.clear();
+        audio.update(delta_time, settings, listener);
+
+        // render every viewport into its own texture
+        let time = rl.get_time() as f32;
+        for (i, rect) in rects.iter().enumerate() {
+            // a failed resize keeps the old view, stretched to fit
+            if !views[i].fits(*rect)
+                && let Some(view) = Viewport::new(rl, thread, *rect)
+            {
+                views[i] = view;
+            }
+            racers[i].camera.target = racers[i].player.position;
+            racers[i].camera.offset = Vector2::new(rect.width / 2.0, rect.height / 2.0);
+            let camera = racers[i].camera;
+
+            with_drawing(&mut rl.begin_texture_mode(thread, &mut views[i].world), |dtex| {
+                with_drawing(&mut dtex.begin_mode2D(camera), |dcam| {
+                    let view_size = Vector2::new(rect.width, rect.height);
+                    background::draw_world(dcam, seed, camera, view_size, &tilemap, all_objects.iter(), atlas.as_ref());
+                    collectibles.draw(dcam, time);
+                    particles.draw(dcam);
+
+                    // every racer is visible everywhere, only your own crosshair is
+                    for (j, racer) in racers.iter().enumerate() {
+                        sprites::draw_player(dcam, atlas.as_ref(), &racer.player, &racer.animator);
+                        if j == i {
+                            let aim = racer.player.grappler.aim_target.map_or(racer.player.position, |t| t.point);
+                            racer.player.draw_grappler(dcam, aim);
+                        } else {
+                            racer.player.grappler.draw_rope(dcam);
+                        }
+                    }
+                });
+            });
+
+            if settings.darkness_enabled {
+                let player = &racers[i].player;
+                let screen_pos = rl.get_world_to_screen2D(player.position, camera);
+                let glows: Vec<(Vector2, f32)> = light::world_lights(&[], &particles, &collectibles)
+                    .into_iter()
+                    .map(|(position, radius)| (rl.get_world_to_screen2D(position, camera), radius))
+                    .collect();
+                with_drawing(&mut rl.begin_texture_mode(thread, &mut views[i].darkness), |dtex| {
+                    let ambient = biome::ambient_color(height_of(player.position));
+                    light::draw_darkness(dtex, ambient, &glows, player, screen_pos, &light_tex, &beam_tex);
+                });
+            }
+        }
+
+        with_drawing(&mut rl.begin_drawing(thread), |d| {
+            d.clear_background(Color::BLACK);
+            for (i, rect) in rects.iter().enumerate() {
+                draw_viewport(d, &racers[i], &views[i], *rect, i, settings.darkness_enabled);
+            }
+            // dividers
+            for rect in rects.iter().skip(1) {
+                d.draw_line_ex(Vector2::new(rect.x, rect.y), Vector2::new(rect.x, rect.y + rect.height), 2.0, Color::GRAY);
+                d.draw_line_ex(Vector2::new(rect.x, rect.y), Vector2::new(rect.x + rect.width, rect.y), 2.0, Color::GRAY);
+            }
+
+            match state {
+                RaceState::Countdown(time) => {
+                    let text = format!("{}", time.ceil() as i32);
+                    d.draw_text(&text, window.x as i32 / 2 - 15, window.y as i32 / 2 - 40, 80, Color::YELLOW);
+                }
+                RaceState::Racing => {
+                    d.draw_text(&format_time(elapsed), window.x as i32 / 2 - 45, 10, 30, Color::WHITE);
+                }
+                RaceState::Results => draw_results(d, &racers, window),
+            }
+        });
+    }
+}
+
+fn draw_viewport(d: &mut impl RaylibDraw, racer: &Racer, view: &Viewport, rect: Rectangle, index: usize, darkness: bool) {
+    // render textures are upside down
+    let source = |texture: &RenderTexture2D| {
+        Rectangle::new(0.0, 0.0, texture.texture.width as f32, -texture.texture.height as f32)
+    };
+    d.draw_texture_pro(&view.world, source(&view.world), rect, Vector2::zero(), 0.0, Color::WHITE);
+    if darkness {
+        with_drawing(&mut d.begin_blend_mode(BlendMode::BLEND_MULTIPLIED), |d| {
+            d.draw_texture_pro(&view.darkness, source(&view.darkness), rect, Vector2::zero(), 0.0, Color::WHITE);
+        });
+    }
+
+    // dim the view while waiting to respawn
+    if let Some(timer) = racer.respawn_timer {
+        let alpha = (timer / RESPAWN_DELAY).clamp(0.0, 1.0);
+        d.draw_rectangle_rec(rect, Color::BLACK.alpha(alpha));
+    }
+
+    let x = rect.x as i32 + 10;
+    let y = rect.y as i32 + 10;
+    let height = height_of(racer.player.position).max(0.0);
+    d.draw_circle(x + 8, y + 10, PLAYER_SIZE, PLAYER_COLORS[index]);
+    d.draw_text(&format!("P{}  {:.0} / {:.0}", index + 1, height, RACE_HEIGHT), x + 24, y, 20, Color::WHITE);
+    if racer.input.keyboard {
+        d.draw_text("keyboard", x + 24, y + 22, 16, Color::GRAY);
+    } else {
+        d.draw_text(&format!("gamepad {}", racer.input.gamepad + 1), x + 24, y + 22, 16, Color::GRAY);
+    }
+}
+
+fn draw_results(d: &mut impl RaylibDraw, racers: &[Racer], window: Vector2) {
+    d.draw_rectangle(0, 0, window.x as i32, window.y as i32, Color::new(0, 0, 0, 200));
+    d.draw_text("RACE RESULTS", 40, 30, 40, Color::WHITE);
+
+    for (place, &index) in standings(racers).iter().enumerate() {
+        let racer = &racers[index];
+        let result = match racer.finish_time {
+            Some(time) => format_time(time),
+            None => format!("{:.0} height", racer.progress.best_height),
+        };
+        let y = 100 + place as i32 * 40;
+        let color = if place == 0 { Color::GOLD } else { Color::LIGHTGRAY };
+        d.draw_circle(50, y + 12, PLAYER_SIZE, PLAYER_COLORS[index]);
+        d.draw_text(&format!("{}. P{}  {}", place + 1, index + 1, result), 70, y, 30, color);
+    }
+
+    d.draw_text("ENTER/A rematch, ESC/B quit", 40, window.y as i32 - 30, 16, Color::GRAY);
+}
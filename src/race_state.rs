@@ -0,0 +1,54 @@
+use crate::input::MenuInput;
+
+pub const COUNTDOWN: f32 = 3.0;
+
+// where a split-screen race is, the racers only simulate while Racing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RaceState {
+    Countdown(f32),
+    Racing,
+    Results,
+}
+
+// what the race loop has to do after this frame's input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaceAction {
+    Continue,
+    Restart,
+    Leave,
+}
+
+impl RaceState {
+    pub fn new() -> Self {
+        RaceState::Countdown(COUNTDOWN)
+    }
+
+    pub fn update(&mut self, delta: f32, menu_requested: bool, menu: Option<MenuInput>) -> RaceAction {
+        match *self {
+            RaceState::Results => match menu {
+                Some(MenuInput::Confirm) => {
+                    *self = RaceState::new();
+                    RaceAction::Restart
+                }
+                Some(MenuInput::Back) => RaceAction::Leave,
+                _ => RaceAction::Continue,
+            },
+            // escape ends the race early, the press is spent here so the results don't also read it as back
+            _ if menu_requested => {
+                *self = RaceState::Results;
+                RaceAction::Continue
+            }
+            RaceState::Countdown(time) => {
+                *self = if time - delta <= 0.0 { RaceState::Racing } else { RaceState::Countdown(time - delta) };
+                RaceAction::Continue
+            }
+            RaceState::Racing => RaceAction::Continue,
+        }
+    }
+}
+
+impl Default for RaceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
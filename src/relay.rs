@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Instant;
+
+use crate::net::{MAX_PACKET_SIZE, PEER_TIMEOUT, PROTOCOL_VERSION, Packet, is_quiet};
+
+pub const DEFAULT_RELAY_ADDR: &str = "127.0.0.1:7777";
+
+struct RelayPeer {
+    id: u32,
+    seed: u64,
+    last_seen: Instant,
+}
+
+// forwards every player's state to the others racing the same seed
+pub struct Relay {
+    socket: UdpSocket,
+    peers: HashMap<SocketAddr, RelayPeer>,
+    next_id: u32,
+}
+
+impl Relay {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        // wake up now and then to drop silent peers
+        socket.set_read_timeout(Some(PEER_TIMEOUT / 5))?;
+        Ok(Self { socket, peers: HashMap::new(), next_id: 1 })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn serve(&mut self) -> io::Result<()> {
+        loop {
+            self.step()?;
+        }
+    }
+
+    // handles at most one datagram
+    pub fn step(&mut self) -> io::Result<()> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        match self.socket.recv_from(&mut buffer) {
+            Ok((size, from)) => self.handle(&buffer[..size], from),
+            Err(err) if is_quiet(&err) => {}
+            Err(err) => return Err(err),
+        }
+        self.drop_silent_peers();
+        Ok(())
+    }
+
+    fn handle(&mut self, bytes: &[u8], from: SocketAddr) {
+        match Packet::decode(bytes) {
+            Some(Packet::State { version, seed, state }) if version == PROTOCOL_VERSION => {
+                let next_id = &mut self.next_id;
+                let peer = self.peers.entry(from).or_insert_with(|| {
+                    let id = *next_id;
+                    *next_id += 1;
+                    println!("peer {} joined from {} on seed {}", id, from, seed);
+                    RelayPeer { id, seed, last_seen: Instant::now() }
+                });
+                let old_seed = std::mem::replace(&mut peer.seed, seed);
+                peer.last_seen = Instant::now();
+                let id = peer.id;
+                // whoever was racing the old seed would otherwise keep a frozen ghost until the timeout
+                if old_seed != seed {
+                    println!("peer {} moved to seed {}", id, seed);
+                    self.broadcast(&Packet::Left { id }, from, old_seed);
+                }
+                self.broadcast(&Packet::Peer { id, state }, from, seed);
+            }
+            Some(Packet::Leave) => {
+                if let Some(peer) = self.peers.remove(&from) {
+                    println!("peer {} left", peer.id);
+                    self.broadcast(&Packet::Left { id: peer.id }, from, peer.seed);
+                }
+            }
+            // old clients and garbage are ignored
+            _ => {}
+        }
+    }
+
+    fn drop_silent_peers(&mut self) {
+        let silent: Vec<SocketAddr> = self
+            .peers
+            .iter()
+            .filter(|(_, peer)| peer.last_seen.elapsed() > PEER_TIMEOUT)
+            .map(|(addr, _)| *addr)
+            .collect();
+        for addr in silent {
+            let peer = self.peers.remove(&addr).unwrap();
+            println!("peer {} timed out", peer.id);
+            self.broadcast(&Packet::Left { id: peer.id }, addr, peer.seed);
+        }
+    }
+
+    fn broadcast(&self, packet: &Packet, from: SocketAddr, seed: u64) {
+        let bytes = match packet.encode() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                eprintln!("could not encode packet: {}", err);
+                return;
+            }
+        };
+        for (addr, peer) in &self.peers {
+            if *addr != from && peer.seed == seed {
+                // udp is lossy anyway, a failed send is just a dropped packet
+                let _ = self.socket.send_to(&bytes, addr);
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::Achievements;
+use crate::ghost::GhostRun;
+use crate::modes::GameMode;
+use crate::shop::Wardrobe;
+use crate::speedrun::SplitRecord;
+use crate::stats::Stats;
+use crate::upgrades::Unlocks;
+
+pub const SAVE_PATH: &str = "darkswing_save.json";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    pub best_height: f32,
+    pub ghosts: HashMap<u64, GhostRun>, // best run for each seed
+    pub unlocks: Unlocks,
+    pub splits: HashMap<u64, SplitRecord>, // personal best speedrun splits for each seed
+    pub high_scores: HashMap<GameMode, f32>,
+    pub stats: Stats,
+    pub achievements: Achievements,
+    pub shards: u32, // currency picked up in the layers
+    pub wardrobe: Wardrobe,
+}
+
+impl SaveData {
+    // missing file means nothing has been recorded yet
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => match serde_json::from_str(&text) {
+                Ok(save) => save,
+                Err(err) => {
+                    eprintln!("could not parse {}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string(self) {
+            Ok(text) => {
+                if let Err(err) = std::fs::write(path, text) {
+                    eprintln!("could not write {}: {}", path, err);
+                }
+            }
+            Err(err) => eprintln!("could not serialize save data: {}", err),
+        }
+    }
+
+    pub fn ghost(&self, seed: u64) -> Option<&GhostRun> {
+        self.ghosts.get(&seed)
+    }
+
+    // keeps the run only if it beats what is stored, returns whether anything changed
+    pub fn record_run(&mut self, seed: u64, run: &GhostRun) -> bool {
+        let mut changed = false;
+        if run.best_height > self.best_height {
+            self.best_height = run.best_height;
+            changed = true;
+        }
+
+        let beats_ghost = self.ghosts.get(&seed).is_none_or(|ghost| run.best_height > ghost.best_height);
+        if beats_ghost && !run.positions.is_empty() {
+            self.ghosts.insert(seed, run.clone());
+            changed = true;
+        }
+        changed
+    }
+
+    // endless has no finish, its score is the best height
+    pub fn high_score(&self, mode: GameMode) -> Option<f32> {
+        match mode {
+            GameMode::Endless => (self.best_height > 0.0).then_some(self.best_height),
+            _ => self.high_scores.get(&mode).copied(),
+        }
+    }
+
+    pub fn record_score(&mut self, mode: GameMode, score: f32) -> bool {
+        let new_best = self.high_score(mode).is_none_or(|best| mode.better(score, best));
+        if new_best {
+            self.high_scores.insert(mode, score);
+        }
+        new_best
+    }
+
+    pub fn record_splits(&mut self, seed: u64, record: &SplitRecord) -> bool {
+        let beats_pb = self.splits.get(&seed).is_none_or(|pb| record.beats(pb));
+        if beats_pb && !record.times.is_empty() {
+            self.splits.insert(seed, record.clone());
+        }
+        beats_pb && !record.times.is_empty()
+    }
+}
@@ -0,0 +1,96 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::input::Keybindings;
+
+pub const SETTINGS_PATH: &str = "darkswing.toml";
+
+pub const RESOLUTIONS: [(i32, i32); 5] = [(800, 600), (1024, 768), (1280, 720), (1600, 900), (1920, 1080)];
+pub const FPS_CAPS: [u32; 5] = [30, 60, 120, 144, 0]; // 0 = uncapped
+pub const SPLIT_INTERVALS: [u32; 4] = [250, 500, 1000, 2000];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub darkness_enabled: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub window_width: i32,
+    pub window_height: i32,
+    pub fps_cap: u32,
+    pub vsync: bool,
+    pub fullscreen: bool,
+    pub show_ghost: bool,
+    pub aim_assist: f32, // 0 = off, 1 = widest cone
+    pub speedrun: bool,
+    pub split_interval: u32, // height between speedrun splits
+    pub keybindings: Keybindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            darkness_enabled: true,
+            master_volume: 1.0,
+            music_volume: 0.7,
+            sfx_volume: 0.8,
+            window_width: 800,
+            window_height: 600,
+            fps_cap: 60,
+            vsync: false,
+            fullscreen: false,
+            show_ghost: true,
+            aim_assist: 0.0,
+            speedrun: false,
+            split_interval: 1000,
+            keybindings: Keybindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    // missing file means first launch, a broken one falls back to defaults
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => match toml::from_str(&text) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    eprintln!("could not parse {}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        match toml::to_string_pretty(self) {
+            Ok(text) => {
+                if let Err(err) = std::fs::write(path, text) {
+                    eprintln!("could not write {}: {}", path, err);
+                }
+            }
+            Err(err) => eprintln!("could not serialize settings: {}", err),
+        }
+    }
+
+    // push window related settings to raylib
+    pub fn apply(&self, rl: &mut RaylibHandle) {
+        rl.set_target_fps(self.fps_cap);
+
+        let vsync = WindowState::default().set_vsync_hint(true);
+        if self.vsync {
+            rl.set_window_state(vsync);
+        } else {
+            rl.clear_window_state(vsync);
+        }
+
+        if !rl.is_window_fullscreen() {
+            rl.set_window_size(self.window_width, self.window_height);
+        }
+        if self.fullscreen != rl.is_window_fullscreen() {
+            rl.toggle_fullscreen();
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use raylib::prelude::*;
+
+use crate::input::{Action, Binding, MenuInput, menu_input};
+use crate::settings::{FPS_CAPS, RESOLUTIONS, SPLIT_INTERVALS, Settings};
+
+const STEP: f32 = 0.1; // volume and aim assist step
+const LINE_HEIGHT: i32 = 24;
+const FONT_SIZE: i32 = 20;
+const LIST_TOP: i32 = 90;
+
+#[derive(PartialEq, Clone, Copy)]
+enum MenuItem {
+    Darkness,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Resolution,
+    FpsCap,
+    Vsync,
+    Fullscreen,
+    Ghost,
+    AimAssist,
+    Speedrun,
+    SplitInterval,
+    Bind(Action),
+    Quit,
+    Back,
+}
+
+pub enum MenuEvent {
+    Changed,
+    Quit,
+}
+
+pub struct SettingsMenu {
+    pub open: bool,
+    selected: usize,
+    rebinding: Option<Action>,
+}
+
+fn menu_items() -> Vec<MenuItem> {
+    let mut items = vec![
+        MenuItem::Darkness,
+        MenuItem::MasterVolume,
+        MenuItem::MusicVolume,
+        MenuItem::SfxVolume,
+        MenuItem::Resolution,
+        MenuItem::FpsCap,
+        MenuItem::Vsync,
+        MenuItem::Fullscreen,
+        MenuItem::Ghost,
+        MenuItem::AimAssist,
+        MenuItem::Speedrun,
+        MenuItem::SplitInterval,
+    ];
+    items.extend(Action::ALL.iter().map(|action| MenuItem::Bind(*action)));
+    items.push(MenuItem::Quit);
+    items.push(MenuItem::Back);
+    items
+}
+
+fn step_fraction(value: f32, direction: f32) -> f32 {
+    // round so repeated steps don't drift away from tenths
+    ((value + STEP * direction).clamp(0.0, 1.0) * 10.0).round() / 10.0
+}
+
+fn cycle<T: PartialEq + Copy>(options: &[T], current: T, direction: i32) -> T {
+    let index = options.iter().position(|o| *o == current).unwrap_or(0) as i32;
+    let next = (index + direction).rem_euclid(options.len() as i32);
+    options[next as usize]
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "ON" } else { "OFF" }
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+            rebinding: None,
+        }
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle, settings: &mut Settings) -> Option<MenuEvent> {
+        // waiting for a key to bind
+        if let Some(action) = self.rebinding {
+            let binding = if let Some(key) = rl.get_key_pressed() {
+                if key == KeyboardKey::KEY_ESCAPE {
+                    self.rebinding = None;
+                    return None;
+                }
+                Binding::from_key(key)
+            } else if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                Some(Binding::Mouse(MouseButton::MOUSE_BUTTON_LEFT))
+            } else if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+                Some(Binding::Mouse(MouseButton::MOUSE_BUTTON_RIGHT))
+            } else {
+                None
+            };
+
+            if let Some(binding) = binding {
+                settings.keybindings.set(action, binding);
+                self.rebinding = None;
+                return Some(MenuEvent::Changed);
+            }
+            return None;
+        }
+
+        let items = menu_items();
+        let (direction, confirm) = match menu_input(rl) {
+            Some(MenuInput::Back) => {
+                self.open = false;
+                return None;
+            }
+            Some(MenuInput::Down) => {
+                self.selected = (self.selected + 1) % items.len();
+                return None;
+            }
+            Some(MenuInput::Up) => {
+                self.selected = (self.selected + items.len() - 1) % items.len();
+                return None;
+            }
+            Some(MenuInput::Right) => (1, false),
+            Some(MenuInput::Left) => (-1, false),
+            Some(MenuInput::Confirm) => (0, true),
+            None => return None,
+        };
+        // enter steps values forward like right does
+        let step = if direction == 0 { 1 } else { direction };
+
+        match items[self.selected] {
+            MenuItem::Darkness => settings.darkness_enabled = !settings.darkness_enabled,
+            MenuItem::MasterVolume => settings.master_volume = step_fraction(settings.master_volume, step as f32),
+            MenuItem::MusicVolume => settings.music_volume = step_fraction(settings.music_volume, step as f32),
+            MenuItem::SfxVolume => settings.sfx_volume = step_fraction(settings.sfx_volume, step as f32),
+            MenuItem::Resolution => {
+                let next = cycle(&RESOLUTIONS, (settings.window_width, settings.window_height), step);
+                settings.window_width = next.0;
+                settings.window_height = next.1;
+            }
+            MenuItem::FpsCap => settings.fps_cap = cycle(&FPS_CAPS, settings.fps_cap, step),
+            MenuItem::Vsync => settings.vsync = !settings.vsync,
+            MenuItem::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            MenuItem::Ghost => settings.show_ghost = !settings.show_ghost,
+            MenuItem::AimAssist => settings.aim_assist = step_fraction(settings.aim_assist, step as f32),
+            MenuItem::Speedrun => settings.speedrun = !settings.speedrun,
+            MenuItem::SplitInterval => settings.split_interval = cycle(&SPLIT_INTERVALS, settings.split_interval, step),
+            MenuItem::Bind(action) => {
+                if confirm {
+                    self.rebinding = Some(action);
+                }
+                return None;
+            }
+            MenuItem::Quit => {
+                if confirm {
+                    return Some(MenuEvent::Quit);
+                }
+                return None;
+            }
+            MenuItem::Back => {
+                if confirm {
+                    self.open = false;
+                }
+                return None;
+            }
+        }
+
+        Some(MenuEvent::Changed)
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, settings: &Settings, screen_size: Vector2) {
+        d.draw_rectangle(0, 0, screen_size.x as i32, screen_size.y as i32, Color::new(0, 0, 0, 200));
+        d.draw_text("SETTINGS", 40, 30, 40, Color::WHITE);
+
+        // scroll so the selected line stays between the title and the hint
+        let items = menu_items();
+        let visible = ((screen_size.y as i32 - LIST_TOP - 40) / LINE_HEIGHT).max(1) as usize;
+        let first = self.selected.saturating_sub(visible - 1);
+        for (i, item) in items.iter().enumerate().skip(first).take(visible) {
+            let text = match item {
+                MenuItem::Darkness => format!("Darkness: {}", on_off(settings.darkness_enabled)),
+                MenuItem::MasterVolume => format!("Master volume: {:.0}%", settings.master_volume * 100.0),
+                MenuItem::MusicVolume => format!("Music volume: {:.0}%", settings.music_volume * 100.0),
+                MenuItem::SfxVolume => format!("SFX volume: {:.0}%", settings.sfx_volume * 100.0),
+                MenuItem::Resolution => format!("Resolution: {}x{}", settings.window_width, settings.window_height),
+                MenuItem::FpsCap => {
+                    if settings.fps_cap == 0 {
+                        "FPS cap: uncapped".to_string()
+                    } else {
+                        format!("FPS cap: {}", settings.fps_cap)
+                    }
+                }
+                MenuItem::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+                MenuItem::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+                MenuItem::Ghost => format!("Best run ghost: {}", on_off(settings.show_ghost)),
+                MenuItem::AimAssist => {
+                    if settings.aim_assist <= 0.0 {
+                        "Aim assist: OFF".to_string()
+                    } else {
+                        format!("Aim assist: {:.0}%", settings.aim_assist * 100.0)
+                    }
+                }
+                MenuItem::Speedrun => format!("Speedrun timer: {}", on_off(settings.speedrun)),
+                MenuItem::SplitInterval => format!("Split every: {} height", settings.split_interval),
+                MenuItem::Bind(action) => {
+                    if self.rebinding == Some(*action) {
+                        format!("{}: press a key (ESC to cancel)", action.label())
+                    } else {
+                        format!("{}: {}", action.label(), settings.keybindings.describe(*action))
+                    }
+                }
+                MenuItem::Quit => "Quit game".to_string(),
+                MenuItem::Back => "Back".to_string(),
+            };
+
+            let color = if i == self.selected { Color::YELLOW } else { Color::LIGHTGRAY };
+            let y = LIST_TOP + (i - first) as i32 * LINE_HEIGHT;
+            if i == self.selected {
+                d.draw_text(">", 40, y, FONT_SIZE, color);
+            }
+            d.draw_text(&text, 60, y, FONT_SIZE, color);
+        }
+
+        d.draw_text(
+            "UP/DOWN select, LEFT/RIGHT change, ENTER/A toggle or rebind, ESC/B close",
+            40,
+            screen_size.y as i32 - 30,
+            16,
+            Color::GRAY,
+        );
+    }
+}
+
+impl Default for SettingsMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,144 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::Player;
+use crate::save::SaveData;
+use crate::upgrades::{UPGRADE_TREE, Upgrade, node};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Skin {
+    #[default]
+    Classic,
+    Crimson,
+    Emerald,
+    Gold,
+    Violet,
+}
+
+impl Skin {
+    pub const ALL: [Skin; 5] = [Skin::Classic, Skin::Crimson, Skin::Emerald, Skin::Gold, Skin::Violet];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skin::Classic => "Classic",
+            Skin::Crimson => "Crimson",
+            Skin::Emerald => "Emerald",
+            Skin::Gold => "Gold",
+            Skin::Violet => "Violet",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Skin::Classic => Color::BLUE,
+            Skin::Crimson => Color::new(200, 30, 50, 255),
+            Skin::Emerald => Color::new(30, 180, 90, 255),
+            Skin::Gold => Color::GOLD,
+            Skin::Violet => Color::new(150, 60, 220, 255),
+        }
+    }
+
+    pub fn price(&self) -> u32 {
+        match self {
+            Skin::Classic => 0,
+            Skin::Crimson | Skin::Emerald => 15,
+            Skin::Violet => 30,
+            Skin::Gold => 60,
+        }
+    }
+}
+
+// bought skins and the one being worn, classic is always owned
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Wardrobe {
+    pub owned: Vec<Skin>,
+    pub equipped: Skin,
+}
+
+impl Wardrobe {
+    pub fn has(&self, skin: Skin) -> bool {
+        skin == Skin::Classic || self.owned.contains(&skin)
+    }
+
+    pub fn apply(&self, player: &mut Player) {
+        player.color = self.equipped.color();
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ShopItem {
+    Skin(Skin),
+    Upgrade(Upgrade),
+}
+
+impl ShopItem {
+    // skins first, then the upgrade tree in its own order
+    pub fn all() -> Vec<ShopItem> {
+        let skins = Skin::ALL.iter().map(|skin| ShopItem::Skin(*skin));
+        skins.chain(UPGRADE_TREE.into_iter().map(ShopItem::Upgrade)).collect()
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ShopItem::Skin(skin) => format!("{} skin", skin.name()),
+            ShopItem::Upgrade(upgrade) => node(*upgrade).name.to_string(),
+        }
+    }
+
+    // buying an upgrade skips the climb, so it costs more the higher it would unlock
+    pub fn price(&self) -> u32 {
+        match self {
+            ShopItem::Skin(skin) => skin.price(),
+            ShopItem::Upgrade(upgrade) => (node(*upgrade).unlock_height / 100.0) as u32,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Purchase {
+    Bought,
+    Equipped, // an owned skin was picked again
+    Owned,
+    TooExpensive,
+    Locked, // the upgrade's parent isn't unlocked yet
+}
+
+// spends shards from the save, the caller decides when to write it
+pub fn buy(save_data: &mut SaveData, item: ShopItem) -> Purchase {
+    let price = item.price();
+    match item {
+        ShopItem::Skin(skin) => {
+            let wardrobe = &mut save_data.wardrobe;
+            if wardrobe.has(skin) {
+                if wardrobe.equipped == skin {
+                    return Purchase::Owned;
+                }
+                wardrobe.equipped = skin;
+                return Purchase::Equipped;
+            }
+            if save_data.shards < price {
+                return Purchase::TooExpensive;
+            }
+            save_data.shards -= price;
+            wardrobe.owned.push(skin);
+            wardrobe.equipped = skin;
+            Purchase::Bought
+        }
+        ShopItem::Upgrade(upgrade) => {
+            let unlocks = &mut save_data.unlocks;
+            if unlocks.has(upgrade) {
+                return Purchase::Owned;
+            }
+            if node(upgrade).requires.is_some_and(|parent| !unlocks.has(parent)) {
+                return Purchase::Locked;
+            }
+            if save_data.shards < price {
+                return Purchase::TooExpensive;
+            }
+            save_data.shards -= price;
+            unlocks.unlocked.push(upgrade);
+            Purchase::Bought
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use raylib::prelude::*;
+
+use crate::events::{EventQueue, GameEvent};
+use crate::grappler::GrapplerState;
+use crate::input::InputState;
+use crate::object::{Object, ObjectKind};
+use crate::player::{PLAYER_SIZE, Player, UpdateState};
+
+// one frame of player physics, the same order the game runs it in
+pub fn step(
+    player: &mut Player,
+    delta_time: f32,
+    input: &InputState,
+    objects: &[Object],
+    is_respawning: bool,
+    events: &mut EventQueue,
+) -> UpdateState {
+    let before = Snapshot::of(player);
+    player.input(delta_time, input, objects);
+    let mut update_state = player.update(delta_time, is_respawning);
+    for object in objects {
+        player.update_touch_ground(object);
+    }
+    // collisions push the player out, so hazards count from just outside
+    let hazard = objects
+        .iter()
+        .any(|object| object.kind == ObjectKind::Hazard && object.touches_circle(player.position, PLAYER_SIZE + 1.0));
+    if hazard {
+        update_state.fell = true;
+    }
+    emit_events(&before, player, &update_state, is_respawning, events);
+    update_state
+}
+
+// what the player looked like when the tick started
+struct Snapshot {
+    grounded: bool,
+    fall_speed: f32,
+    grapple_state: GrapplerState,
+    touching_wall: bool,
+}
+
+impl Snapshot {
+    fn of(player: &Player) -> Self {
+        Self {
+            grounded: player.grounded,
+            fall_speed: player.velocity.y.max(0.0),
+            grapple_state: player.grappler.state,
+            touching_wall: player.wall.is_some(),
+        }
+    }
+}
+
+fn attached(state: GrapplerState) -> bool {
+    matches!(state, GrapplerState::Grappled | GrapplerState::Pulling)
+}
+
+fn emit_events(before: &Snapshot, player: &Player, update_state: &UpdateState, is_respawning: bool, events: &mut EventQueue) {
+    let position = player.position;
+    let state = player.grappler.state;
+
+    // firing and hitting can happen in the same tick when the target is close
+    if before.grapple_state == GrapplerState::Idle && state != GrapplerState::Idle {
+        events.push(GameEvent::HookFired { position });
+    }
+    if !attached(before.grapple_state) && attached(state) {
+        events.push(GameEvent::HookAttached { point: player.grappler.grapple_point, origin: position });
+    }
+    if before.grapple_state != GrapplerState::Retracting && state == GrapplerState::Retracting {
+        events.push(GameEvent::HookMissed { position });
+    }
+    if attached(before.grapple_state) && !attached(state) {
+        events.push(GameEvent::HookReleased { position });
+    }
+
+    if player.jumped {
+        events.push(GameEvent::Jumped { position });
+    }
+    if player.grounded && !before.grounded {
+        events.push(GameEvent::Landed { position, speed: before.fall_speed });
+    }
+    if let Some(wall) = player.wall
+        && !before.touching_wall
+        && !player.grounded
+    {
+        events.push(GameEvent::WallContact { position, side: wall.side });
+    }
+    // falls keep being reported until the respawn, only the first one counts
+    if update_state.fell && !is_respawning {
+        events.push(GameEvent::Fell { position });
+    }
+}
+
+// a player and a fixed set of objects, runs without a window
+// the game streams layers in and calls step directly, tests use this
+pub struct Simulation {
+    pub player: Player,
+    pub objects: Vec<Object>,
+    pub time: f32,
+    pub events: EventQueue, // piles up until cleared
+}
+
+impl Simulation {
+    pub fn new(position: Vector2, objects: Vec<Object>) -> Self {
+        Self {
+            player: Player::new(position),
+            objects,
+            time: 0.0,
+            events: EventQueue::new(),
+        }
+    }
+
+    pub fn step(&mut self, delta_time: f32, input: &InputState) -> UpdateState {
+        self.time += delta_time;
+        step(&mut self.player, delta_time, input, &self.objects, false, &mut self.events)
+    }
+
+    // steps with the same input until the time runs out
+    pub fn run(&mut self, seconds: f32, delta_time: f32, input: &InputState) {
+        let steps = (seconds / delta_time).round() as usize;
+        for _ in 0..steps {
+            self.step(delta_time, input);
+        }
+    }
+}
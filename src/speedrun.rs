@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::InputState;
+
+pub const SPLITS_PATH: &str = "darkswing_splits.csv";
+
+// split times of one run, times[i] is when height (i + 1) * interval was reached
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SplitRecord {
+    pub interval: f32,
+    pub times: Vec<f32>,
+}
+
+impl SplitRecord {
+    // more milestones wins, then the faster last one
+    pub fn beats(&self, other: &SplitRecord) -> bool {
+        if self.interval != other.interval || self.times.len() != other.times.len() {
+            return self.times.len() > other.times.len();
+        }
+        match (self.times.last(), other.times.last()) {
+            (Some(mine), Some(theirs)) => mine < theirs,
+            _ => false,
+        }
+    }
+}
+
+pub struct Speedrun {
+    pub interval: f32,
+    pub started: bool,
+    pub elapsed: f32,
+    pub splits: Vec<f32>,
+    pub personal_best: Option<SplitRecord>,
+}
+
+impl Speedrun {
+    pub fn new(interval: f32, personal_best: Option<SplitRecord>) -> Self {
+        // splits against a different interval can't be compared
+        let personal_best = personal_best.filter(|record| record.interval == interval);
+        Self {
+            interval,
+            started: false,
+            elapsed: 0.0,
+            splits: Vec::new(),
+            personal_best,
+        }
+    }
+
+    // the clock starts on the first input, returns the index of a split reached this frame
+    pub fn update(&mut self, delta_time: f32, input: &InputState, height: f32) -> Option<usize> {
+        if !self.started {
+            if !input.any_action() {
+                return None;
+            }
+            self.started = true;
+        }
+        self.elapsed += delta_time;
+
+        if height >= self.next_milestone() {
+            self.splits.push(self.elapsed);
+            return Some(self.splits.len() - 1);
+        }
+        None
+    }
+
+    pub fn next_milestone(&self) -> f32 {
+        self.milestone(self.splits.len())
+    }
+
+    pub fn milestone(&self, index: usize) -> f32 {
+        (index + 1) as f32 * self.interval
+    }
+
+    pub fn pb_split(&self, index: usize) -> Option<f32> {
+        self.personal_best.as_ref()?.times.get(index).copied()
+    }
+
+    // negative when ahead of the personal best
+    pub fn delta(&self, index: usize) -> Option<f32> {
+        Some(self.splits.get(index)? - self.pb_split(index)?)
+    }
+
+    pub fn record(&self) -> SplitRecord {
+        SplitRecord { interval: self.interval, times: self.splits.clone() }
+    }
+
+    // csv with a comment header, see the readme for the columns
+    pub fn export(&self, path: &str, seed: u64) {
+        let mut text = format!("# darkswing splits, seed {}, every {} units\n", seed, self.interval);
+        text.push_str("split,height,time,pb_time,delta\n");
+        for (index, time) in self.splits.iter().enumerate() {
+            let pb = self.pb_split(index).map_or(String::new(), |pb| format!("{:.3}", pb));
+            let delta = self.delta(index).map_or(String::new(), |delta| format!("{:+.3}", delta));
+            let _ = writeln!(text, "{},{},{:.3},{},{}", index + 1, self.milestone(index), time, pb, delta);
+        }
+
+        if let Err(err) = std::fs::write(path, text) {
+            eprintln!("could not write {}: {}", path, err);
+        }
+    }
+}
+
+// m:ss.cc
+pub fn format_time(seconds: f32) -> String {
+    let centis = (seconds.max(0.0) * 100.0) as u32;
+    format!("{}:{:02}.{:02}", centis / 6000, centis / 100 % 60, centis % 100)
+}
+
+// +s.cc / -s.cc
+pub fn format_delta(seconds: f32) -> String {
+    let sign = if seconds < 0.0 { '-' } else { '+' };
+    format!("{}{:.2}", sign, seconds.abs())
+}
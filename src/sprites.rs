@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use raylib::prelude::*;
+use serde::Deserialize;
+
+use crate::biome::Theme;
+use crate::grappler::GrapplerState;
+use crate::object::{Object, ObjectKind};
+use crate::player::{PLAYER_SIZE, Player};
+
+pub const ATLAS_PATH: &str = "assets/atlas.json";
+const PLAYER_HEIGHT: f32 = PLAYER_SIZE * 3.0; // sprites are drawn taller than the hitbox
+const RUN_SPEED: f32 = 10.0; // slower than this on the ground counts as standing
+
+// where each frame sits in the packed texture and how frames make up animations
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AtlasDescriptor {
+    pub texture: String, // relative to the descriptor
+    pub frames: HashMap<String, [f32; 4]>, // x, y, width, height in pixels
+    #[serde(default)]
+    pub animations: HashMap<String, AnimationDesc>,
+    #[serde(default)]
+    pub nine_slices: HashMap<String, NineSliceDesc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AnimationDesc {
+    pub frames: Vec<String>,
+    pub fps: f32,
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+// a frame whose corners stay put while the edges and middle stretch
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NineSliceDesc {
+    pub frame: String,
+    pub border: i32,
+}
+
+impl AtlasDescriptor {
+    pub fn parse(text: &str) -> Result<AtlasDescriptor, String> {
+        let desc: AtlasDescriptor = serde_json::from_str(text).map_err(|err| err.to_string())?;
+        // every name has to point at a frame, better to fail here than draw nothing later
+        for (name, animation) in &desc.animations {
+            if animation.frames.is_empty() {
+                return Err(format!("animation {} has no frames", name));
+            }
+            if let Some(missing) = animation.frames.iter().find(|frame| !desc.frames.contains_key(*frame)) {
+                return Err(format!("animation {} uses unknown frame {}", name, missing));
+            }
+        }
+        for (name, slice) in &desc.nine_slices {
+            if !desc.frames.contains_key(&slice.frame) {
+                return Err(format!("nine slice {} uses unknown frame {}", name, slice.frame));
+            }
+        }
+        Ok(desc)
+    }
+
+    pub fn frame(&self, name: &str) -> Option<Rectangle> {
+        self.frames.get(name).map(|[x, y, w, h]| Rectangle::new(*x, *y, *w, *h))
+    }
+}
+
+impl AnimationDesc {
+    pub fn frame_at(&self, time: f32) -> &str {
+        let index = (time * self.fps).max(0.0) as usize;
+        let index = if self.looping { index % self.frames.len() } else { index.min(self.frames.len() - 1) };
+        &self.frames[index]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PlayerAnimation {
+    Idle,
+    Run,
+    Jump,
+    Swing,
+    WallSlide,
+}
+
+impl PlayerAnimation {
+    pub fn from_player(player: &Player) -> PlayerAnimation {
+        match player.grappler.state {
+            GrapplerState::Grappled | GrapplerState::Pulling => PlayerAnimation::Swing,
+            _ if player.wall.is_some() && !player.grounded => PlayerAnimation::WallSlide,
+            _ if !player.grounded => PlayerAnimation::Jump,
+            _ if player.velocity.x.abs() > RUN_SPEED => PlayerAnimation::Run,
+            _ => PlayerAnimation::Idle,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlayerAnimation::Idle => "player_idle",
+            PlayerAnimation::Run => "player_run",
+            PlayerAnimation::Jump => "player_jump",
+            PlayerAnimation::Swing => "player_swing",
+            PlayerAnimation::WallSlide => "player_wall_slide",
+        }
+    }
+}
+
+// which animation is playing and for how long
+pub struct Animator {
+    pub animation: PlayerAnimation,
+    pub time: f32,
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self { animation: PlayerAnimation::Idle, time: 0.0 }
+    }
+
+    pub fn update(&mut self, delta_time: f32, player: &Player) {
+        let animation = PlayerAnimation::from_player(player);
+        if animation != self.animation {
+            // a new animation starts from its first frame
+            self.animation = animation;
+            self.time = 0.0;
+        } else {
+            self.time += delta_time;
+        }
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Atlas {
+    pub texture: Texture2D,
+    pub desc: AtlasDescriptor,
+}
+
+impl Atlas {
+    // None means draw with primitives like before
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) -> Option<Atlas> {
+        let text = fs::read_to_string(path).ok()?;
+        let desc = match AtlasDescriptor::parse(&text) {
+            Ok(desc) => desc,
+            Err(err) => {
+                eprintln!("failed to load atlas {}: {}", path, err);
+                return None;
+            }
+        };
+        let texture_path = Path::new(path).parent().unwrap_or(Path::new("")).join(&desc.texture);
+        match rl.load_texture(thread, &texture_path.to_string_lossy()) {
+            Ok(texture) => Some(Atlas { texture, desc }),
+            Err(err) => {
+                eprintln!("failed to load atlas texture {}: {}", texture_path.display(), err);
+                None
+            }
+        }
+    }
+
+    // false when the atlas has no frame for this, so the caller can fall back
+    pub fn draw_player(&self, d: &mut impl RaylibDraw, player: &Player, animator: &Animator) -> bool {
+        let Some(animation) = self.desc.animations.get(animator.animation.name()) else {
+            return false;
+        };
+        let Some(mut source) = self.desc.frame(animation.frame_at(animator.time)) else {
+            return false;
+        };
+        let scale = PLAYER_HEIGHT / source.height;
+        let size = Vector2::new(source.width * scale, PLAYER_HEIGHT);
+        // feet on the bottom of the hitbox
+        let dest = Rectangle::new(
+            player.position.x - size.x / 2.0,
+            player.position.y + PLAYER_SIZE - size.y,
+            size.x,
+            size.y,
+        );
+        // frames face right, a negative width mirrors them
+        if player.facing < 0.0 {
+            source.width = -source.width;
+        }
+        d.draw_texture_pro(&self.texture, source, dest, Vector2::zero(), 0.0, player.color);
+        true
+    }
+
+    pub fn draw_object(&self, d: &mut impl RaylibDraw, object: &Object, theme: &Theme) -> bool {
+        let Some(slice) = self.desc.nine_slices.get(nine_slice_name(object.kind)) else {
+            return false;
+        };
+        let Some(source) = self.desc.frame(&slice.frame) else {
+            return false;
+        };
+        let info = NPatchInfo {
+            source,
+            left: slice.border,
+            top: slice.border,
+            right: slice.border,
+            bottom: slice.border,
+            layout: NPatchLayout::NPATCH_NINE_PATCH,
+        };
+        // the art is grey so the biome still decides the colour
+        let color = theme.object_color(object.kind);
+        d.draw_texture_n_patch(&self.texture, info, object.get_rect(), Vector2::zero(), 0.0, color);
+        true
+    }
+}
+
+pub fn nine_slice_name(kind: ObjectKind) -> &'static str {
+    match kind {
+        ObjectKind::Solid => "object_solid",
+        ObjectKind::Unhookable => "object_unhookable",
+        ObjectKind::Hazard => "object_hazard",
+    }
+}
+
+// the atlas is optional, everything still draws without it
+pub fn draw_player(d: &mut impl RaylibDraw, atlas: Option<&Atlas>, player: &Player, animator: &Animator) {
+    if !atlas.is_some_and(|atlas| atlas.draw_player(d, player, animator)) {
+        player.draw(d);
+    }
+}
+
+pub fn draw_object(d: &mut impl RaylibDraw, atlas: Option<&Atlas>, object: &Object, theme: &Theme) {
+    if !atlas.is_some_and(|atlas| atlas.draw_object(d, object, theme)) {
+        object.draw(d, theme);
+    }
+}
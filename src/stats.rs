@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::biome::theme_at;
+use crate::events::GameEvent;
+use crate::grappler::GrapplerState;
+use crate::layergen::layer_number;
+use crate::light::Visibility;
+use crate::player::Player;
+use crate::progress::height_of;
+
+const DARK_AMBIENT: u8 = 50; // biomes darker than this count as darkness
+
+// totals over every run, kept in the save file
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub hooks_fired: u32,
+    pub hooks_hit: u32,
+    pub distance_swung: f32,
+    pub falls: u32,
+    pub caught: u32, // times something in the dark got the player
+    pub time_in_darkness: f32, // seconds
+    pub max_speed: f32,
+    pub highest_layer: i32,
+}
+
+impl Stats {
+    // share of hooks that caught something
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.hooks_fired > 0).then(|| self.hooks_hit as f32 / self.hooks_fired as f32)
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::HookFired { .. } => self.hooks_fired += 1,
+            GameEvent::HookAttached { .. } => self.hooks_hit += 1,
+            GameEvent::Fell { .. } => self.falls += 1,
+            GameEvent::Caught { .. } => self.caught += 1,
+            _ => {}
+        }
+    }
+
+    // the running totals that don't come from a single event
+    pub fn update(&mut self, delta_time: f32, player: &Player, visibility: &Visibility) {
+        let speed = player.velocity.length();
+        if matches!(player.grappler.state, GrapplerState::Grappled | GrapplerState::Pulling) {
+            self.distance_swung += speed * delta_time;
+        }
+        self.max_speed = self.max_speed.max(speed);
+        // a dark biome, and no lamp or glow around you besides your own light
+        let dark_biome = theme_at(height_of(player.position)).ambient < DARK_AMBIENT;
+        if visibility.darkness && dark_biome && !visibility.in_spot(player.position) {
+            self.time_in_darkness += delta_time;
+        }
+        self.highest_layer = self.highest_layer.max(layer_number(player.position.y));
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::grappler::{GrapplerStats, HookType};
+use crate::player::Player;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Upgrade {
+    LongerRope,
+    FasterHook,
+    FasterReel,
+    LongHook,
+    DoubleHook,
+    BungeeHook,
+    DoubleJump,
+    AirDash,
+}
+
+#[derive(Clone, Copy)]
+pub struct UpgradeNode {
+    pub name: &'static str,
+    pub requires: Option<Upgrade>,
+    pub unlock_height: f32, // best height needed before it unlocks
+}
+
+// every upgrade in the order the shop lists them
+pub const UPGRADE_TREE: [Upgrade; 8] = [
+    Upgrade::LongerRope,
+    Upgrade::LongHook,
+    Upgrade::FasterHook,
+    Upgrade::DoubleHook,
+    Upgrade::FasterReel,
+    Upgrade::BungeeHook,
+    Upgrade::DoubleJump,
+    Upgrade::AirDash,
+];
+
+// each node needs its parent unlocked first, so the tree opens up as you climb
+pub fn node(upgrade: Upgrade) -> UpgradeNode {
+    let (name, requires, unlock_height) = match upgrade {
+        Upgrade::LongerRope => ("Longer rope", None, 500.0),
+        Upgrade::LongHook => ("Long hook", Some(Upgrade::LongerRope), 1500.0),
+        Upgrade::FasterHook => ("Faster hook", None, 1000.0),
+        Upgrade::DoubleHook => ("Double hook", Some(Upgrade::FasterHook), 2500.0),
+        Upgrade::FasterReel => ("Faster reel", None, 2000.0),
+        Upgrade::BungeeHook => ("Bungee hook", Some(Upgrade::FasterReel), 3500.0),
+        Upgrade::DoubleJump => ("Double jump", None, 750.0),
+        Upgrade::AirDash => ("Air dash", Some(Upgrade::DoubleJump), 3000.0),
+    };
+    UpgradeNode { name, requires, unlock_height }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Unlocks {
+    pub unlocked: Vec<Upgrade>,
+}
+
+impl Unlocks {
+    pub fn has(&self, upgrade: Upgrade) -> bool {
+        self.unlocked.contains(&upgrade)
+    }
+
+    // unlocks everything the height allows, returns what is new
+    pub fn unlock_by_height(&mut self, best_height: f32) -> Vec<Upgrade> {
+        let mut new = Vec::new();
+        // repeat so a parent and child can unlock on the same frame
+        loop {
+            let next = UPGRADE_TREE.into_iter().find(|upgrade| {
+                let node = node(*upgrade);
+                !self.has(*upgrade)
+                    && best_height >= node.unlock_height
+                    && node.requires.is_none_or(|parent| self.has(parent))
+            });
+            match next {
+                Some(upgrade) => {
+                    self.unlocked.push(upgrade);
+                    new.push(upgrade);
+                }
+                None => return new,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> GrapplerStats {
+        let mut stats = GrapplerStats::default();
+        if self.has(Upgrade::LongerRope) {
+            stats.rope_length *= 1.25;
+        }
+        if self.has(Upgrade::FasterHook) {
+            stats.grapple_speed *= 1.3;
+        }
+        if self.has(Upgrade::FasterReel) {
+            stats.reel_speed *= 1.3;
+        }
+        stats
+    }
+
+    pub fn hooks(&self) -> Vec<HookType> {
+        let mut hooks = vec![HookType::Standard];
+        let unlockable = [
+            (Upgrade::LongHook, HookType::Long),
+            (Upgrade::DoubleHook, HookType::Double),
+            (Upgrade::BungeeHook, HookType::Bungee),
+        ];
+        for (upgrade, hook) in unlockable {
+            if self.has(upgrade) {
+                hooks.push(hook);
+            }
+        }
+        hooks
+    }
+
+    pub fn apply(&self, player: &mut Player) {
+        player.can_double_jump = self.has(Upgrade::DoubleJump);
+        player.can_dash = self.has(Upgrade::AirDash);
+
+        let grappler = &mut player.grappler;
+        grappler.base_stats = self.stats();
+        grappler.available_hooks = self.hooks();
+        if !grappler.available_hooks.contains(&grappler.hook) {
+            grappler.hook = HookType::Standard;
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use darkswing::audio::{Audio, Mixer, SAMPLE_RATE, Sfx, music_samples, wav_bytes};
+use darkswing::biome::Biome;
+use darkswing::events::GameEvent;
+use darkswing::settings::Settings;
+use raylib::prelude::*;
+
+fn mixer() -> Mixer {
+    let settings = Settings { master_volume: 1.0, sfx_volume: 0.5, music_volume: 0.5, ..Default::default() };
+    Mixer::new(&settings)
+}
+
+#[test]
+fn every_sound_sits_in_its_own_slot() {
+    for (i, sfx) in Sfx::ALL.iter().enumerate() {
+        assert_eq!(*sfx as usize, i, "{}", sfx.name());
+    }
+}
+
+#[test]
+fn generated_sounds_are_short_and_in_range() {
+    for sfx in Sfx::ALL {
+        let samples = sfx.samples();
+        assert!(!samples.is_empty(), "{}", sfx.name());
+        assert!(samples.len() < SAMPLE_RATE as usize, "{}", sfx.name());
+        assert!(samples.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+    }
+    for biome in Biome::ALL {
+        assert!(music_samples(biome).iter().any(|sample| *sample != 0.0));
+    }
+}
+
+#[test]
+fn wav_header_describes_16_bit_mono() {
+    let bytes = wav_bytes(&[0.0, 1.0, -1.0]);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(&bytes[8..12], b"WAVE");
+    assert_eq!(u32::from_le_bytes(bytes[24..28].try_into().unwrap()), SAMPLE_RATE);
+    assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 6);
+    assert_eq!(bytes.len(), 44 + 6);
+    assert_eq!(i16::from_le_bytes([bytes[46], bytes[47]]), i16::MAX);
+}
+
+#[test]
+fn positional_sounds_fade_with_distance_and_pan() {
+    let mut mixer = mixer();
+    mixer.play(Sfx::Jump);
+    mixer.play_at(Sfx::Land, Vector2::new(200.0, 0.0));
+    mixer.play_at(Sfx::Land, Vector2::new(-200.0, 0.0));
+    mixer.play_at(Sfx::Land, Vector2::new(0.0, 5000.0));
+
+    assert_eq!(mixer.cues.len(), 3, "too far away to hear");
+    assert_eq!(mixer.cues[0].volume, 0.5);
+    assert_eq!(mixer.cues[0].pan, 0.0);
+    assert!(mixer.cues[1].volume < 0.5);
+    assert!(mixer.cues[1].pan > 0.0);
+    assert!(mixer.cues[2].pan < 0.0);
+}
+
+#[test]
+fn music_crossfades_with_altitude() {
+    let mut mixer = mixer();
+    for _ in 0..300 {
+        mixer.update_music(1.0 / 60.0, 0.0);
+    }
+    assert_eq!(mixer.music, [1.0, 0.0, 0.0, 0.0]);
+    assert_eq!(mixer.track_volume(0), 0.5);
+
+    // high in the sky the cave track fades out over a couple of seconds
+    mixer.update_music(0.5, 4500.0);
+    assert!(mixer.music[0] > 0.0 && mixer.music[2] > 0.0);
+    for _ in 0..300 {
+        mixer.update_music(1.0 / 60.0, 4500.0);
+    }
+    assert_eq!(mixer.music, [0.0, 0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn null_backend_mixes_without_a_device() {
+    let settings = Settings::default();
+    let mut audio = Audio::new(None, &settings);
+    audio.mixer.play(Sfx::Respawn);
+    audio.update(1.0 / 60.0, &settings, Vector2::zero());
+    assert!(audio.mixer.cues.is_empty());
+    assert!(audio.mixer.music[0] > 0.0);
+}
+
+#[test]
+fn gameplay_events_make_sounds() {
+    let mut mixer = mixer();
+    let position = Vector2::zero();
+    mixer.handle_event(&GameEvent::HookFired { position });
+    mixer.handle_event(&GameEvent::HookAttached { point: Vector2::new(0.0, -100.0), origin: position });
+    mixer.handle_event(&GameEvent::HookReleased { position });
+    mixer.handle_event(&GameEvent::Landed { position, speed: 10.0 });
+    mixer.handle_event(&GameEvent::Landed { position, speed: 400.0 });
+    mixer.handle_event(&GameEvent::NewBestHeight { height: 100.0 });
+
+    let heard: Vec<Sfx> = mixer.cues.iter().map(|cue| cue.sfx).collect();
+    assert_eq!(heard, vec![Sfx::HookFire, Sfx::HookHit, Sfx::RopeSnap, Sfx::Land]);
+}
@@ -0,0 +1,49 @@
+use darkswing::biome::{Biome, TRANSITION, blend_at, theme_at};
+
+#[test]
+fn bands_follow_the_height() {
+    assert_eq!(Biome::at_height(-500.0), Biome::Cave);
+    assert_eq!(Biome::at_height(0.0), Biome::Cave);
+    assert_eq!(Biome::at_height(1499.0), Biome::Cave);
+    assert_eq!(Biome::at_height(1500.0), Biome::Industrial);
+    assert_eq!(Biome::at_height(3500.0), Biome::Sky);
+    assert_eq!(Biome::at_height(100000.0), Biome::Space);
+}
+
+#[test]
+fn blending_happens_only_around_a_band_edge() {
+    assert_eq!(blend_at(500.0), (Biome::Cave, Biome::Cave, 0.0));
+    assert_eq!(blend_at(2500.0), (Biome::Industrial, Biome::Industrial, 0.0));
+
+    let (from, to, t) = blend_at(1500.0);
+    assert_eq!((from, to), (Biome::Cave, Biome::Industrial));
+    assert!((t - 0.5).abs() < 1e-6);
+
+    // the fade starts half a transition below the edge and ends half above it
+    let (_, _, start) = blend_at(1500.0 - TRANSITION / 2.0);
+    assert_eq!(start, 0.0);
+    assert_eq!(blend_at(1500.0 + TRANSITION / 2.0), (Biome::Industrial, Biome::Industrial, 0.0));
+}
+
+#[test]
+fn mixing_fades_colours_and_switches_settings_halfway() {
+    let cave = Biome::Cave.theme();
+    let industrial = Biome::Industrial.theme();
+
+    let start = cave.mix(&industrial, 0.0);
+    assert_eq!(start.ambient, cave.ambient);
+    assert_eq!(start.background.r, cave.background.r);
+    let end = cave.mix(&industrial, 1.0);
+    assert_eq!(end.ambient, industrial.ambient);
+    assert_eq!(end.solid.g, industrial.solid.g);
+
+    let middle = cave.mix(&industrial, 0.5);
+    let halfway = |a: u8, b: u8| (a as f32 + b as f32) / 2.0;
+    assert!((middle.ambient as f32 - halfway(cave.ambient, industrial.ambient)).abs() <= 0.5);
+    assert!((middle.tile.r as f32 - halfway(cave.tile.r, industrial.tile.r)).abs() <= 0.5);
+    // the generator settings don't fade, they belong to one biome or the other
+    assert_eq!(cave.mix(&industrial, 0.49).hazard_chance, cave.hazard_chance);
+    assert_eq!(middle.hazard_chance, industrial.hazard_chance);
+
+    assert_eq!(theme_at(2500.0).ambient, industrial.ambient);
+}
@@ -0,0 +1,86 @@
+use darkswing::collectibles::{CollectibleKind, Collectibles, ROPE_BONUS, generate_collectibles};
+use darkswing::events::{EventQueue, GameEvent};
+use darkswing::input::InputState;
+use darkswing::layergen::generate_layer;
+use darkswing::object::{Object, ObjectKind};
+use darkswing::player::{Player, PlayerTool};
+use raylib::prelude::*;
+
+mod common;
+use common::{cubes};
+
+#[test]
+fn the_same_seed_places_the_same_items() {
+    let layer = generate_layer(7, -1000);
+    assert_eq!(generate_collectibles(7, -1000, &layer), generate_collectibles(7, -1000, &layer));
+
+    // enough cubes that some of them carry something, and not every one does
+    let many = cubes(200);
+    let items = generate_collectibles(7, -1000, &many);
+    assert!(!items.is_empty() && items.len() < many.len());
+    assert!(items.iter().any(|item| item.kind == CollectibleKind::Shard));
+    assert!(items.iter().any(|item| item.kind == CollectibleKind::Battery));
+    assert_ne!(items, generate_collectibles(8, -1000, &many));
+
+    // floating above the middle of their cube
+    for item in items {
+        let cube = &many[item.index];
+        assert_eq!(item.position.x, cube.position.x + 15.0);
+        assert!(item.position.y < cube.position.y);
+    }
+}
+
+#[test]
+fn hazards_carry_nothing() {
+    let spikes: Vec<Object> = cubes(200).into_iter().map(|cube| cube.with_kind(ObjectKind::Hazard)).collect();
+    assert!(generate_collectibles(7, -1000, &spikes).is_empty());
+}
+
+#[test]
+fn picked_up_items_stay_gone_when_the_layer_reloads() {
+    let many = cubes(200);
+    let mut collectibles = Collectibles::new();
+    collectibles.load_layer(7, -1000, &many);
+    let count = collectibles.iter().count();
+    let first = *collectibles.iter().next().unwrap();
+
+    let mut events = EventQueue::new();
+    assert!(collectibles.pick_up(Vector2::new(-5000.0, 0.0), &mut events).is_empty());
+    assert_eq!(collectibles.pick_up(first.position, &mut events), vec![first.kind]);
+    assert_eq!(
+        events.iter().collect::<Vec<_>>(),
+        vec![&GameEvent::Collected { position: first.position, kind: first.kind }]
+    );
+
+    collectibles.unload_layer(-1000);
+    assert_eq!(collectibles.iter().count(), 0);
+    collectibles.load_layer(7, -1000, &many);
+    assert_eq!(collectibles.iter().count(), count - 1);
+}
+
+#[test]
+fn pickups_refill_the_battery_and_lengthen_the_rope() {
+    let mut player = Player::new(Vector2::zero());
+    let rope = player.grappler.stats().rope_length;
+    player.battery = 0.2;
+
+    assert_eq!(CollectibleKind::Shard.apply(&mut player), 1);
+    assert_eq!(CollectibleKind::Battery.apply(&mut player), 0);
+    assert_eq!(player.battery, 1.0);
+    CollectibleKind::RopeUpgrade.apply(&mut player);
+    assert_eq!(player.grappler.stats().rope_length, rope + ROPE_BONUS);
+}
+
+#[test]
+fn the_flashlight_drains_the_battery() {
+    let mut player = Player::new(Vector2::zero());
+    let input = InputState { select_flashlight: true, ..Default::default() };
+    player.input(1.0, &input, &[]);
+    assert_eq!(player.current_tool, PlayerTool::Flashlight);
+    assert!(player.battery < 1.0);
+
+    player.battery = 0.0;
+    player.input(1.0, &InputState::default(), &[]);
+    assert_eq!(player.battery, 0.0);
+    assert!(!player.flashlight_on());
+}
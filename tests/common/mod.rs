@@ -0,0 +1,30 @@
+// fixtures shared by the test files, each one only pulls in what it needs
+#![allow(dead_code)]
+
+use darkswing::input::InputState;
+use darkswing::object::Object;
+use raylib::prelude::*;
+
+pub const DT: f32 = 1.0 / 60.0;
+
+// wide floor with its top just under a player standing at the origin
+pub fn floor() -> Object {
+    Object::new(Vector2::new(-500.0, 10.0), Vector2::new(1000.0, 100.0))
+}
+
+// a row of small cubes 100 units apart
+pub fn cubes(count: usize) -> Vec<Object> {
+    (0..count).map(|i| Object::new(Vector2::new(i as f32 * 100.0, 0.0), Vector2::new(30.0, 30.0))).collect()
+}
+
+pub fn moving() -> InputState {
+    InputState { move_x: 1.0, ..Default::default() }
+}
+
+pub fn jump_pressed() -> InputState {
+    InputState { jump: true, jump_pressed: true, ..Default::default() }
+}
+
+pub fn fire_at(target: Vector2) -> InputState {
+    InputState { fire: true, aim_target: target, ..Default::default() }
+}
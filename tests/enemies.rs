@@ -0,0 +1,130 @@
+use darkswing::enemies::{Enemies, Enemy, EnemyKind, generate_enemies};
+use darkswing::light::{GLOW_RADIUS, Visibility};
+use darkswing::player::{Player, PlayerTool};
+use raylib::prelude::*;
+
+mod common;
+use common::{DT, cubes};
+
+fn enemy(kind: EnemyKind, position: Vector2) -> Enemy {
+    Enemy { kind, position, home: position, patrol: (position.x - 50.0, position.x + 50.0), direction: 1.0 }
+}
+
+// player at the origin holding the flashlight pointed right
+fn flashlight() -> Visibility {
+    let mut player = Player::new(Vector2::zero());
+    player.current_tool = PlayerTool::Flashlight;
+    player.flashlight_direction = Vector2::new(1.0, 0.0);
+    Visibility::new(&player, Vec::new(), true)
+}
+
+fn glow() -> Visibility {
+    Visibility::new(&Player::new(Vector2::zero()), Vec::new(), true)
+}
+
+#[test]
+fn spawns_are_seeded_and_leave_the_start_alone() {
+    let many = cubes(200);
+    assert!(generate_enemies(7, 200, &many).is_empty());
+    assert!(generate_enemies(7, -200, &many).is_empty());
+
+    let high = generate_enemies(7, -4000, &many);
+    assert_eq!(high, generate_enemies(7, -4000, &many));
+    assert_ne!(high, generate_enemies(8, -4000, &many));
+    for kind in [EnemyKind::Crawler, EnemyKind::Flyer, EnemyKind::Shade] {
+        assert!(high.iter().any(|enemy| enemy.kind == kind), "no {:?}", kind);
+    }
+    // crawlers stand on their cube, the rest float above it
+    for enemy in high {
+        let cube = &many[(enemy.position.x / 100.0) as usize];
+        assert!(enemy.position.y < cube.position.y);
+        assert!(enemy.patrol.0 >= cube.position.x && enemy.patrol.1 <= cube.position.x + cube.size.x);
+    }
+}
+
+#[test]
+fn visibility_follows_the_glow_and_the_beam() {
+    let glow = glow();
+    assert!(glow.is_lit(Vector2::new(0.0, GLOW_RADIUS - 1.0)));
+    assert!(!glow.is_lit(Vector2::new(0.0, GLOW_RADIUS + 1.0)));
+
+    let beam = flashlight();
+    assert!(beam.in_beam(Vector2::new(300.0, 50.0)));
+    assert!(!beam.in_beam(Vector2::new(300.0, 150.0)), "outside the cone");
+    assert!(!beam.in_beam(Vector2::new(-100.0, 0.0)), "behind the player");
+    assert!(!beam.is_lit(Vector2::new(0.0, 50.0)), "no glow while the beam is on");
+
+    let lamp = Visibility::new(&Player::new(Vector2::zero()), vec![(Vector2::new(1000.0, 0.0), 100.0)], true);
+    assert!(lamp.is_lit(Vector2::new(950.0, 0.0)));
+}
+
+#[test]
+fn crawlers_patrol_in_the_dark_and_freeze_in_the_light() {
+    let mut crawler = enemy(EnemyKind::Crawler, Vector2::new(1000.0, 0.0));
+    let visibility = glow();
+    for _ in 0..120 {
+        crawler.update(DT, Vector2::zero(), &visibility);
+        assert!((950.0..=1050.0).contains(&crawler.position.x));
+    }
+    assert_eq!(crawler.direction, -1.0, "turned at the edge");
+
+    let mut lit = enemy(EnemyKind::Crawler, Vector2::new(50.0, 0.0));
+    lit.update(DT, Vector2::zero(), &visibility);
+    assert_eq!(lit.position.x, 50.0);
+}
+
+#[test]
+fn flyers_hunt_from_the_dark_and_back_off_when_lit() {
+    let start = Vector2::new(0.0, -300.0);
+    let mut flyer = enemy(EnemyKind::Flyer, start);
+    let visibility = glow();
+    flyer.update(1.0, Vector2::zero(), &visibility);
+    assert!(flyer.position.y > start.y, "closing in");
+
+    // inside the glow it heads home
+    flyer.position = Vector2::new(0.0, -100.0);
+    flyer.update(0.5, Vector2::zero(), &visibility);
+    assert!(flyer.position.y < -100.0);
+
+    // too far away to notice
+    let far = Vector2::new(0.0, -1000.0);
+    let mut idle = enemy(EnemyKind::Flyer, far);
+    idle.update(1.0, Vector2::zero(), &visibility);
+    assert_eq!(idle.position, far);
+}
+
+#[test]
+fn shades_flee_the_beam() {
+    let visibility = flashlight();
+    let mut shade = enemy(EnemyKind::Shade, Vector2::new(200.0, 0.0));
+    shade.update(0.5, Vector2::zero(), &visibility);
+    assert!(shade.position.x > 200.0, "running from the beam");
+
+    let mut behind = enemy(EnemyKind::Shade, Vector2::new(-200.0, 0.0));
+    behind.update(0.5, Vector2::zero(), &visibility);
+    assert!(behind.position.x > -200.0, "creeping up from behind");
+}
+
+#[test]
+fn touching_an_enemy_catches_the_player() {
+    let mut enemies = Enemies::new();
+    enemies.layers.insert(0, vec![enemy(EnemyKind::Shade, Vector2::new(0.0, -300.0))]);
+    let visibility = glow();
+    assert!(!enemies.update(DT, Vector2::zero(), &visibility));
+    assert!(enemies.update(DT, Vector2::new(0.0, -300.0), &visibility));
+}
+
+#[test]
+fn nothing_lurks_with_the_darkness_off() {
+    let visibility = Visibility::new(&Player::new(Vector2::zero()), Vec::new(), false);
+    assert!(visibility.is_lit(Vector2::new(5000.0, 5000.0)));
+
+    let mut crawler = enemy(EnemyKind::Crawler, Vector2::new(1000.0, 0.0));
+    crawler.update(1.0, Vector2::zero(), &visibility);
+    assert_eq!(crawler.position.x, 1000.0);
+
+    let start = Vector2::new(0.0, -300.0);
+    let mut flyer = enemy(EnemyKind::Flyer, start);
+    flyer.update(1.0, Vector2::zero(), &visibility);
+    assert_eq!(flyer.position, start);
+}
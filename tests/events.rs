@@ -0,0 +1,136 @@
+use darkswing::events::{EventQueue, GameEvent};
+use darkswing::input::InputState;
+use darkswing::object::{Object, ObjectKind};
+use darkswing::player::WallSide;
+use darkswing::progress::{Progress, RECORD_STEP};
+use darkswing::sim::Simulation;
+use raylib::prelude::*;
+
+mod common;
+use common::{DT, fire_at, floor, jump_pressed};
+
+// only the kind of each event, in order, positions vary too much to compare
+fn kinds(sim: &Simulation) -> Vec<&'static str> {
+    sim.events
+        .iter()
+        .map(|event| match event {
+            GameEvent::HookFired { .. } => "fired",
+            GameEvent::HookAttached { .. } => "attached",
+            GameEvent::HookMissed { .. } => "missed",
+            GameEvent::HookReleased { .. } => "released",
+            GameEvent::Jumped { .. } => "jumped",
+            GameEvent::Landed { .. } => "landed",
+            GameEvent::WallContact { .. } => "wall",
+            GameEvent::Fell { .. } => "fell",
+            GameEvent::Caught { .. } => "caught",
+            GameEvent::Respawned { .. } => "respawned",
+            GameEvent::NewBestHeight { .. } => "best",
+            GameEvent::CheckpointReached { .. } => "checkpoint",
+            GameEvent::Collected { .. } => "collected",
+        })
+        .collect()
+}
+
+#[test]
+fn jumping_and_landing_are_reported_once() {
+    let mut sim = Simulation::new(Vector2::zero(), vec![floor()]);
+    sim.step(DT, &InputState::default());
+    sim.events.clear();
+
+    sim.step(DT, &jump_pressed());
+    sim.run(2.0, DT, &InputState::default());
+    assert_eq!(kinds(&sim), vec!["jumped", "landed"]);
+
+    let landed = sim.events.iter().find_map(|event| match event {
+        GameEvent::Landed { speed, .. } => Some(*speed),
+        _ => None,
+    });
+    // the speed it came down with, not the zero after landing
+    assert!(landed.unwrap() > 100.0, "landing speed {:?}", landed);
+}
+
+#[test]
+fn hooks_fire_attach_and_release() {
+    let ceiling = Object::new(Vector2::new(-500.0, -150.0), Vector2::new(1000.0, 20.0));
+    let mut sim = Simulation::new(Vector2::zero(), vec![ceiling]);
+    sim.player.grounded = false;
+
+    sim.step(DT, &fire_at(Vector2::new(0.0, -200.0)));
+    sim.run(0.3, DT, &InputState::default());
+    assert_eq!(kinds(&sim), vec!["fired", "attached"]);
+    let point = sim.events.iter().find_map(|event| match event {
+        GameEvent::HookAttached { point, .. } => Some(*point),
+        _ => None,
+    });
+    let point = point.unwrap();
+    assert!((-150.0..=-130.0).contains(&point.y), "hooked at {:?}", point);
+
+    sim.events.clear();
+    sim.step(DT, &jump_pressed());
+    assert_eq!(kinds(&sim), vec!["released"]);
+}
+
+#[test]
+fn hooks_that_hit_nothing_miss() {
+    let mut sim = Simulation::new(Vector2::zero(), Vec::new());
+    sim.player.grounded = false;
+    sim.step(DT, &fire_at(Vector2::new(0.0, -200.0)));
+    sim.run(0.5, DT, &InputState::default());
+    assert_eq!(kinds(&sim), vec!["fired", "missed"]);
+}
+
+#[test]
+fn touching_a_wall_in_the_air_is_reported() {
+    let wall = Object::new(Vector2::new(20.0, -2000.0), Vector2::new(100.0, 4000.0));
+    let mut sim = Simulation::new(Vector2::zero(), vec![wall]);
+    sim.player.grounded = false;
+    sim.run(0.5, DT, &InputState { move_x: 1.0, ..Default::default() });
+
+    let walls: Vec<&GameEvent> = sim.events.iter().filter(|event| matches!(event, GameEvent::WallContact { .. })).collect();
+    assert_eq!(walls.len(), 1);
+    assert!(matches!(walls[0], GameEvent::WallContact { side: WallSide::Right, .. }));
+}
+
+#[test]
+fn hazards_report_a_fall() {
+    let spikes = floor().with_kind(ObjectKind::Hazard);
+    let mut sim = Simulation::new(Vector2::zero(), vec![spikes]);
+    sim.step(DT, &InputState::default());
+    assert!(kinds(&sim).contains(&"fell"));
+}
+
+#[test]
+fn progress_reports_new_heights_and_checkpoints() {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), Vec::new());
+    let mut progress = Progress::starting_at(Vector2::zero());
+    let mut events = EventQueue::new();
+
+    sim.player.position = Vector2::new(0.0, -50.0);
+    progress.update(&sim.player, &mut events);
+    progress.update(&sim.player, &mut events);
+    assert_eq!(events.iter().count(), 1, "the same height isn't new twice");
+
+    let checkpoint = Vector2::new(0.0, -60.0);
+    progress.touch_checkpoints(checkpoint, &[checkpoint], &mut events);
+    progress.touch_checkpoints(checkpoint, &[checkpoint], &mut events);
+    let reached: Vec<&GameEvent> =
+        events.iter().filter(|event| matches!(event, GameEvent::CheckpointReached { .. })).collect();
+    assert_eq!(reached, vec![&GameEvent::CheckpointReached { position: checkpoint }]);
+}
+
+#[test]
+fn a_long_climb_announces_the_record_a_few_times_not_every_frame() {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), Vec::new());
+    let mut progress = Progress::starting_at(Vector2::zero());
+    let mut events = EventQueue::new();
+
+    // a unit higher every frame for a thousand frames
+    for frame in 1..=1000 {
+        sim.player.position = Vector2::new(0.0, -(frame as f32));
+        progress.update(&sim.player, &mut events);
+    }
+    let announced = events.iter().filter(|event| matches!(event, GameEvent::NewBestHeight { .. })).count();
+    assert!(announced >= 1);
+    assert!(announced <= 1 + (1000.0 / RECORD_STEP) as usize, "{} announcements", announced);
+    assert_eq!(progress.best_height, 1000.0);
+}
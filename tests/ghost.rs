@@ -0,0 +1,24 @@
+use darkswing::ghost::{GhostRecorder, MAX_SAMPLES, RECORD_INTERVAL};
+use raylib::prelude::*;
+
+#[test]
+fn recordings_replay_where_the_player_was() {
+    let mut recorder = GhostRecorder::new();
+    for i in 0..60 {
+        recorder.record(RECORD_INTERVAL, Vector2::new(i as f32, -(i as f32)));
+    }
+    assert_eq!(recorder.run.positions.len(), 60);
+    assert_eq!(recorder.run.best_height, 59.0);
+    let halfway = recorder.run.position_at(30.5 * RECORD_INTERVAL).unwrap();
+    assert!((halfway.x - 30.5).abs() < 0.01);
+    assert!(recorder.run.position_at(recorder.run.duration() + 1.0).is_none());
+}
+
+#[test]
+fn long_lives_stop_recording_at_the_cap() {
+    let mut recorder = GhostRecorder::new();
+    // one long frame covering more samples than the cap allows
+    recorder.record((MAX_SAMPLES as f32 + 100.0) * RECORD_INTERVAL, Vector2::zero());
+    recorder.record(1.0, Vector2::zero());
+    assert_eq!(recorder.run.positions.len(), MAX_SAMPLES);
+}
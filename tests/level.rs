@@ -0,0 +1,161 @@
+use darkswing::layergen::{LayerProfile, generate_layer};
+use darkswing::level::{LEVEL_VERSION, Level, LevelLight, LevelObject, ProceduralSection};
+use darkswing::object::{Object, ObjectKind};
+
+fn sample() -> Level {
+    let mut level = Level::new();
+    level.objects.push(LevelObject { position: [0.0, -300.0], size: [120.0, 20.0], kind: ObjectKind::Unhookable });
+    level.objects.push(LevelObject { position: [200.0, -500.0], size: [40.0, 40.0], kind: ObjectKind::Hazard });
+    level.lights.push(LevelLight { position: [60.0, -350.0], radius: 180.0 });
+    level.checkpoints.push([60.0, -330.0]);
+    level.procedural.push(ProceduralSection {
+        bottom: -800.0,
+        top: Some(-3000.0),
+        seed: Some(7),
+        profile: LayerProfile::Dense,
+    });
+    level.procedural.push(level.section_above());
+    level
+}
+
+fn error_line(text: &str) -> usize {
+    Level::parse(text).unwrap_err().line
+}
+
+#[test]
+fn round_trips_through_json() {
+    let level = sample();
+    let parsed = Level::parse(&level.to_json()).unwrap();
+    assert_eq!(parsed, level);
+    // and again, so nothing drifts on re-save
+    assert_eq!(parsed.to_json(), level.to_json());
+}
+
+#[test]
+fn round_trips_through_a_file() {
+    let path = std::env::temp_dir().join(format!("darkswing_level_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let level = sample();
+    level.save(path).unwrap();
+    let loaded = Level::load(path);
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded.unwrap(), level);
+}
+
+#[test]
+fn optional_parts_can_be_left_out() {
+    let level = Level::parse(r#"{ "version": 1, "spawn": [0, 0] }"#).unwrap();
+    assert!(level.objects.is_empty());
+    assert!(level.lights.is_empty());
+    assert!(level.procedural.is_empty());
+
+    let level = Level::parse(r#"{ "version": 1, "spawn": [0, 0], "objects": [{ "position": [0, 50], "size": [10, 10] }], "procedural": [{ "bottom": -100 }] }"#).unwrap();
+    assert_eq!(level.objects[0].kind, ObjectKind::Solid);
+    assert_eq!(level.procedural[0].profile, LayerProfile::Normal);
+    assert_eq!(level.procedural[0].top, None);
+}
+
+#[test]
+fn unversioned_files_are_migrated() {
+    let text = r#"{
+        "spawn": [100, 100],
+        "objects": [{ "position": [50, 400], "size": [300, 50], "kind": "Solid" }],
+        "checkpoints": [],
+        "procedural_above": true
+    }"#;
+    let level = Level::parse(text).unwrap();
+    assert_eq!(level.version, LEVEL_VERSION);
+    assert_eq!(level.procedural, vec![level.section_above()]);
+}
+
+#[test]
+fn syntax_errors_report_their_line() {
+    let text = "{\n  \"version\": 1,\n  \"spawn\": [0, 0],\n  \"objects\": [\n    { \"position\": [0, 0] \"size\": [1, 1] }\n  ]\n}";
+    assert_eq!(error_line(text), 5);
+}
+
+#[test]
+fn unknown_fields_and_kinds_report_their_line() {
+    let text = "{\n  \"version\": 1,\n  \"spawn\": [0, 0],\n  \"objectz\": []\n}";
+    assert_eq!(error_line(text), 4);
+
+    let text = "{\n  \"version\": 1,\n  \"spawn\": [0, 0],\n  \"objects\": [\n    { \"position\": [0, 50], \"size\": [1, 1], \"kind\": \"Lava\" }\n  ]\n}";
+    let err = Level::parse(text).unwrap_err();
+    assert_eq!(err.line, 5);
+    assert!(err.message.contains("Lava"), "{}", err.message);
+}
+
+#[test]
+fn invalid_values_report_their_line() {
+    let text = "{\n  \"version\": 1,\n  \"spawn\": [0, 0],\n  \"objects\": [\n    { \"position\": [0, 50], \"size\": [10, 10] },\n    { \"position\": [0, 90],\n      \"size\": [0, 20] }\n  ]\n}";
+    let err = Level::parse(text).unwrap_err();
+    assert_eq!(err.line, 7);
+    assert!(err.message.contains("object 1"), "{}", err.message);
+
+    let level = sample();
+    let mut broken = level.clone();
+    broken.lights[0].radius = -5.0;
+    let err = Level::parse(&broken.to_json()).unwrap_err();
+    assert!(err.message.contains("light 0"), "{}", err.message);
+    assert!(err.line > 0);
+
+    let mut broken = level.clone();
+    broken.procedural[0].top = Some(0.0);
+    let err = Level::parse(&broken.to_json()).unwrap_err();
+    assert!(err.message.contains("procedural section 0"), "{}", err.message);
+    assert!(err.line > 0);
+}
+
+#[test]
+fn spawn_inside_an_object_is_rejected() {
+    let text = "{\n  \"version\": 1,\n  \"objects\": [{ \"position\": [0, 0], \"size\": [100, 100] }],\n  \"spawn\": [50, 50]\n}";
+    let err = Level::parse(text).unwrap_err();
+    assert_eq!(err.line, 4);
+    assert_eq!(err.to_string(), "line 4: spawn is inside an object");
+}
+
+#[test]
+fn newer_versions_are_refused() {
+    let text = format!("{{\n  \"spawn\": [0, 0],\n  \"version\": {}\n}}", LEVEL_VERSION + 1);
+    let err = Level::parse(&text).unwrap_err();
+    assert_eq!(err.line, 3);
+    assert!(err.message.contains("newer"), "{}", err.message);
+}
+
+#[test]
+fn versions_past_u32_are_not_wrapped_around() {
+    // 2^32 + 1 used to truncate to version 1 and load as the current format
+    let text = "{\n  \"spawn\": [0, 0],\n  \"version\": 4294967297\n}";
+    let err = Level::parse(text).unwrap_err();
+    assert_eq!(err.line, 3);
+    assert!(err.message.contains("4294967297"), "{}", err.message);
+}
+
+#[test]
+fn procedural_sections_pick_their_own_seed_and_profile() {
+    let level = sample();
+    // inside the dense section with a fixed seed, whatever the run's seed is
+    let dense = level.generate_layer(1, -1000).unwrap();
+    assert_eq!(dense.len(), LayerProfile::Dense.cubes_per_layer());
+    let other_run = level.generate_layer(2, -1000).unwrap();
+    assert_eq!(dense[0].position, other_run[0].position);
+
+    // between the tower and the first section there's nothing
+    assert!(level.generate_layer(1, -600).is_none());
+}
+
+#[test]
+fn generated_sections_get_the_biome_mix() {
+    // industrial heights, with the same layout the endless tower uses there
+    let mut level = Level::new();
+    level.procedural.push(ProceduralSection { bottom: -1600.0, top: Some(-3400.0), seed: None, profile: LayerProfile::Dense });
+
+    let kinds = |objects: Vec<Object>| objects.iter().map(|object| object.kind).collect::<Vec<_>>();
+    let mut special = 0;
+    for layer in (-3300..=-1700).step_by(100) {
+        let from_level = kinds(level.generate_layer(7, layer).unwrap());
+        assert_eq!(from_level, kinds(generate_layer(7, layer)));
+        special += from_level.iter().filter(|kind| **kind != ObjectKind::Solid).count();
+    }
+    assert!(special > 0);
+}
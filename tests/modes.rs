@@ -0,0 +1,84 @@
+use darkswing::input::InputState;
+use darkswing::modes::{GameMode, ModeRun, TARGET_HEIGHT, TIME_LIMIT};
+use raylib::prelude::*;
+
+mod common;
+use common::{DT, moving};
+
+// height is measured upward, so a position at -h is h units up
+fn at_height(height: f32) -> Vector2 {
+    Vector2::new(0.0, -height)
+}
+
+#[test]
+fn the_clock_waits_for_the_first_input() {
+    let mut run = ModeRun::new(GameMode::TimeAttack);
+    for _ in 0..60 {
+        assert_eq!(run.update(DT, &InputState::default(), at_height(0.0)), None);
+    }
+    assert!(!run.started);
+    assert_eq!(run.elapsed, 0.0);
+
+    run.update(DT, &moving(), at_height(0.0));
+    assert!(run.started);
+    // once going it keeps counting without input
+    run.update(DT, &InputState::default(), at_height(0.0));
+    assert!((run.elapsed - 2.0 * DT).abs() < 1e-6);
+}
+
+#[test]
+fn target_height_scores_the_time_taken() {
+    let mut run = ModeRun::new(GameMode::TargetHeight);
+    assert_eq!(run.update(1.0, &moving(), at_height(TARGET_HEIGHT - 1.0)), None);
+    assert_eq!(run.update(1.0, &moving(), at_height(TARGET_HEIGHT)), Some(2.0));
+
+    // a faster time is the better score
+    assert!(GameMode::TargetHeight.better(30.0, 40.0));
+    assert!(!GameMode::TargetHeight.better(50.0, 40.0));
+}
+
+#[test]
+fn time_attack_scores_the_best_height_when_time_runs_out() {
+    let mut run = ModeRun::new(GameMode::TimeAttack);
+    run.update(1.0, &moving(), at_height(800.0));
+    // falling back down doesn't lose the best height
+    assert_eq!(run.update(TIME_LIMIT / 2.0, &moving(), at_height(100.0)), None);
+    assert_eq!(run.time_left(), Some(TIME_LIMIT / 2.0 - 1.0));
+    assert_eq!(run.update(TIME_LIMIT, &moving(), at_height(100.0)), Some(800.0));
+    assert_eq!(run.time_left(), Some(0.0));
+    assert!(GameMode::TimeAttack.better(900.0, 800.0));
+}
+
+#[test]
+fn rising_darkness_speeds_up_and_ends_when_it_reaches_you() {
+    let mut run = ModeRun::new(GameMode::RisingDarkness);
+    let start = run.void_y;
+    run.update(1.0, &moving(), at_height(1000.0));
+    let first_second = start - run.void_y;
+    let before = run.void_y;
+    run.update(1.0, &moving(), at_height(1000.0));
+    assert!(first_second > 0.0);
+    assert!(before - run.void_y > first_second, "the void should rise faster over time");
+
+    // standing just above it is fine, touching it ends the run with the best height
+    let above = Vector2::new(0.0, run.void_y - 100.0);
+    assert_eq!(run.update(DT, &moving(), above), None);
+    let below = Vector2::new(0.0, run.void_y + 100.0);
+    assert_eq!(run.update(DT, &moving(), below), Some(1000.0));
+}
+
+#[test]
+fn respawns_stay_above_the_rising_void() {
+    let spawn = Vector2::new(100.0, 100.0);
+    let mut run = ModeRun::new(GameMode::RisingDarkness);
+    run.void_y = -2000.0;
+    let respawn = run.respawn_point(spawn);
+    assert_eq!(respawn.x, spawn.x);
+    assert!(respawn.y < run.void_y);
+
+    // other modes and a void still far below leave the spawn alone
+    run.void_y = 5000.0;
+    assert_eq!(run.respawn_point(spawn).y, spawn.y);
+    let endless = ModeRun::new(GameMode::Endless);
+    assert_eq!(endless.respawn_point(spawn).y, spawn.y);
+}
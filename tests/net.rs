@@ -0,0 +1,135 @@
+use std::net::UdpSocket;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use darkswing::net::{NetClient, PROTOCOL_VERSION, Packet, PlayerState};
+use darkswing::relay::Relay;
+use darkswing::player::PlayerTool;
+
+// relay on a free localhost port, running until the test process exits
+fn start_relay() -> String {
+    let mut relay = Relay::bind("127.0.0.1:0").unwrap();
+    let addr = relay.local_addr().unwrap().to_string();
+    thread::spawn(move || relay.serve());
+    addr
+}
+
+fn state(tick: u32, x: f32) -> PlayerState {
+    PlayerState {
+        tick,
+        position: [x, -200.0],
+        velocity: [10.0, 0.0],
+        rope: Some([x, -300.0]),
+        second_rope: None,
+        tool: PlayerTool::Grapple,
+        flashlight_direction: [0.0, -1.0],
+    }
+}
+
+// two clients the relay knows about, both have seen each other once
+fn connected_pair(relay: &str, seed: u64) -> (NetClient, NetClient) {
+    let mut alice = NetClient::connect(relay, seed).unwrap();
+    let mut bob = NetClient::connect(relay, seed).unwrap();
+    // udp may drop the first datagrams, so keep saying hello
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while alice.peers.is_empty() || bob.peers.is_empty() {
+        assert!(Instant::now() < deadline, "nothing arrived through the relay");
+        alice.send_state(state(0, 0.0));
+        bob.send_state(state(0, 0.0));
+        thread::sleep(Duration::from_millis(10));
+        alice.poll();
+        bob.poll();
+    }
+    (alice, bob)
+}
+
+#[test]
+fn packets_round_trip() {
+    let packet = Packet::Peer { id: 3, state: state(7, 12.5) };
+    assert_eq!(Packet::decode(&packet.encode().unwrap()), Some(packet));
+    assert_eq!(Packet::decode(b"not a packet"), None);
+}
+
+#[test]
+fn relay_forwards_state_to_peers_on_the_same_seed() {
+    let relay = start_relay();
+    let (mut alice, mut bob) = connected_pair(&relay, 42);
+
+    alice.send_state(state(1, 50.0));
+    thread::sleep(Duration::from_millis(50));
+    bob.poll();
+
+    assert_eq!(bob.peers.len(), 1);
+    let seen = bob.peers.values().next().unwrap();
+    assert_eq!(seen.state.position, [50.0, -200.0]);
+    assert_eq!(seen.state.rope, Some([50.0, -300.0]));
+}
+
+#[test]
+fn other_seeds_are_not_forwarded() {
+    let relay = start_relay();
+    let mut alice = NetClient::connect(&relay, 1).unwrap();
+    let mut bob = NetClient::connect(&relay, 2).unwrap();
+
+    for tick in 0..20 {
+        bob.send_state(state(tick, 0.0));
+        alice.send_state(state(tick, 0.0));
+        thread::sleep(Duration::from_millis(5));
+    }
+    alice.poll();
+    bob.poll();
+    assert!(alice.peers.is_empty());
+    assert!(bob.peers.is_empty());
+}
+
+#[test]
+fn stale_ticks_are_ignored_and_leaving_removes_the_peer() {
+    let relay = start_relay();
+    let (mut alice, mut bob) = connected_pair(&relay, 42);
+
+    alice.send_state(state(10, 50.0));
+    thread::sleep(Duration::from_millis(50));
+    bob.poll();
+    assert_eq!(bob.peers.values().next().unwrap().state.tick, 10);
+
+    // an older datagram arriving late doesn't move the ghost back
+    alice.send_state(state(5, -50.0));
+    thread::sleep(Duration::from_millis(50));
+    bob.poll();
+    assert_eq!(bob.peers.values().next().unwrap().state.tick, 10);
+
+    drop(alice);
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while !bob.peers.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+        bob.poll();
+    }
+    assert!(bob.peers.is_empty());
+}
+
+#[test]
+fn switching_seeds_leaves_the_old_race() {
+    let relay = start_relay();
+    let mut bob = NetClient::connect(&relay, 42).unwrap();
+    // a bare socket so the same address can send on two seeds
+    let alice = UdpSocket::bind("127.0.0.1:0").unwrap();
+    alice.connect(&relay).unwrap();
+    let hello = |seed: u64| Packet::State { version: PROTOCOL_VERSION, seed, state: state(0, 0.0) }.encode().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while bob.peers.is_empty() {
+        assert!(Instant::now() < deadline, "nothing arrived through the relay");
+        bob.send_state(state(0, 0.0));
+        alice.send(&hello(42)).unwrap();
+        thread::sleep(Duration::from_millis(10));
+        bob.poll();
+    }
+
+    alice.send(&hello(7)).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while !bob.peers.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+        bob.poll();
+    }
+    assert!(bob.peers.is_empty());
+}
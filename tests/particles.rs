@@ -0,0 +1,100 @@
+use darkswing::events::GameEvent;
+use darkswing::input::InputState;
+use darkswing::object::Object;
+use darkswing::particles::{
+    DUST, Emitter, MAX_PARTICLES, ParticlePool, ParticleStyle, PlayerEffects, SPARKS, handle_event,
+};
+use darkswing::sim::Simulation;
+use raylib::prelude::*;
+
+const DT: f32 = 1.0 / 60.0;
+
+#[test]
+fn particles_fade_and_die_after_their_life() {
+    let mut pool = ParticlePool::new();
+    pool.burst(Vector2::zero(), Vector2::new(0.0, -1.0), 10, &DUST);
+    assert_eq!(pool.particles().len(), 10);
+
+    pool.update(DUST.life / 2.0);
+    let particle = pool.particles()[0];
+    assert!(particle.color().a < DUST.start_color.a);
+    assert!(particle.size() > DUST.start_size && particle.size() < DUST.end_size);
+
+    pool.update(DUST.life);
+    assert!(pool.particles().is_empty());
+}
+
+#[test]
+fn gravity_and_drag_move_particles() {
+    let mut pool = ParticlePool::new();
+    let style = ParticleStyle { speed: (0.0, 0.0), drag: 0.0, ..SPARKS };
+    pool.emit(Vector2::zero(), Vector2::new(1.0, 0.0), &style);
+    pool.update(0.1);
+    assert!(pool.particles()[0].position.y > 0.0, "sparks fall");
+}
+
+#[test]
+fn a_full_pool_drops_new_particles() {
+    let mut pool = ParticlePool::new();
+    pool.burst(Vector2::zero(), Vector2::new(0.0, -1.0), MAX_PARTICLES + 50, &DUST);
+    assert_eq!(pool.particles().len(), MAX_PARTICLES);
+}
+
+#[test]
+fn emitters_spawn_at_their_rate_only_while_active() {
+    let mut pool = ParticlePool::new();
+    let mut emitter = Emitter::new(20.0, DUST);
+    emitter.update(1.0, &mut pool);
+    assert!(pool.particles().is_empty());
+
+    emitter.active = true;
+    for _ in 0..10 {
+        emitter.update(0.05, &mut pool);
+    }
+    assert_eq!(pool.particles().len(), 10);
+}
+
+#[test]
+fn only_glowing_particles_give_light() {
+    let mut pool = ParticlePool::new();
+    pool.burst(Vector2::zero(), Vector2::new(0.0, -1.0), 5, &DUST);
+    pool.burst(Vector2::zero(), Vector2::new(0.0, -1.0), 3, &SPARKS);
+    let lights: Vec<_> = pool.lights().collect();
+    assert_eq!(lights.len(), 3);
+    assert!(lights.iter().all(|(_, radius)| *radius == SPARKS.light));
+}
+
+#[test]
+fn only_hard_landings_kick_up_dust() {
+    let mut pool = ParticlePool::new();
+    handle_event(&GameEvent::Landed { position: Vector2::zero(), speed: 50.0 }, &mut pool);
+    assert!(pool.particles().is_empty());
+    handle_event(&GameEvent::Landed { position: Vector2::zero(), speed: 400.0 }, &mut pool);
+    assert!(!pool.particles().is_empty());
+}
+
+#[test]
+fn hook_hits_throw_sparks_at_the_anchor() {
+    let mut pool = ParticlePool::new();
+    let anchor = Vector2::new(0.0, -100.0);
+    handle_event(&GameEvent::HookAttached { point: anchor, origin: Vector2::zero() }, &mut pool);
+    assert!(!pool.particles().is_empty());
+    assert!(pool.particles().iter().all(|particle| particle.position == anchor));
+    assert!(pool.lights().count() > 0);
+}
+
+#[test]
+fn wall_slides_scrape_dust_while_they_last() {
+    let wall = Object::new(Vector2::new(20.0, -500.0), Vector2::new(50.0, 1000.0));
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), vec![wall]);
+    sim.player.grounded = false;
+    let mut effects = PlayerEffects::new();
+    let mut pool = ParticlePool::new();
+    let push_right = InputState { move_x: 1.0, ..Default::default() };
+    for _ in 0..30 {
+        sim.step(DT, &push_right);
+        effects.update(DT, &sim.player, &mut pool);
+    }
+    assert!(sim.player.wall.is_some());
+    assert!(!pool.particles().is_empty());
+}
@@ -0,0 +1,455 @@
+use darkswing::grappler::{Grappler, GrapplerState, HookType, find_aim_target};
+use darkswing::input::InputState;
+use darkswing::object::{Object, ObjectKind};
+use darkswing::player::{MAX_SWING_SPEED, WallSide};
+use darkswing::sim::Simulation;
+use darkswing::upgrades::{UPGRADE_TREE, Unlocks, Upgrade, node};
+use raylib::prelude::*;
+
+mod common;
+use common::{DT, fire_at, floor, jump_pressed};
+
+// player hanging under an anchor 100 units up, moving sideways at the bottom of the swing
+fn swinging(velocity: Vector2) -> Simulation {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), Vec::new());
+    let player = &mut sim.player;
+    player.velocity = velocity;
+    player.grounded = false;
+    player.grappler.grapple_point = Vector2::new(0.0, -100.0);
+    player.grappler.length = 100.0;
+    player.grappler.state = GrapplerState::Grappled;
+    sim
+}
+
+// player resting on a wide floor
+fn standing() -> Simulation {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), vec![floor()]);
+    sim.step(DT, &InputState::default());
+    assert!(sim.player.grounded);
+    sim
+}
+
+fn falling() -> Simulation {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), Vec::new());
+    sim.player.grounded = false;
+    sim
+}
+
+fn jump_held() -> InputState {
+    InputState { jump: true, ..Default::default() }
+}
+
+#[test]
+fn release_keeps_tangential_velocity() {
+    let mut sim = swinging(Vector2::new(300.0, 0.0));
+    sim.player.grappler.base_stats.release_boost = 0.0;
+
+    sim.step(DT, &InputState::default());
+    let before = sim.player.velocity;
+    assert!(before.x > 250.0, "swing lost its speed: {:?}", before);
+
+    sim.step(DT, &jump_pressed());
+    assert_eq!(sim.player.grappler.state, GrapplerState::Idle);
+    assert!((sim.player.velocity.x - before.x).abs() < 1e-3, "{:?} vs {:?}", sim.player.velocity, before);
+}
+
+#[test]
+fn release_boost_adds_speed_along_motion() {
+    let mut sim = swinging(Vector2::new(300.0, 0.0));
+    let boost = sim.player.grappler.stats().release_boost;
+    assert!(boost > 0.0);
+
+    sim.step(DT, &InputState::default());
+    let before = sim.player.velocity;
+    sim.step(DT, &jump_pressed());
+
+    assert!(sim.player.velocity.x > before.x + boost * 0.9);
+}
+
+#[test]
+fn momentum_carries_after_release() {
+    let mut sim = swinging(Vector2::new(300.0, 0.0));
+    sim.player.grappler.base_stats.release_boost = 0.0;
+    sim.step(DT, &jump_pressed());
+    let start = sim.player.position;
+
+    // no input in the air, so nothing should brake the launch
+    sim.run(0.5, DT, &InputState::default());
+    assert!(sim.player.velocity.x > 250.0);
+    assert!(sim.player.position.x - start.x > 120.0);
+}
+
+#[test]
+fn pulling_to_the_point_keeps_velocity() {
+    let mut sim = swinging(Vector2::new(200.0, 0.0));
+    sim.player.grappler.state = GrapplerState::Pulling;
+    sim.player.grappler.length = 5.0;
+
+    sim.step(DT, &InputState::default());
+    assert_eq!(sim.player.grappler.state, GrapplerState::Idle);
+    assert!(sim.player.velocity.length() > 100.0, "{:?}", sim.player.velocity);
+}
+
+#[test]
+fn reeling_in_speeds_up_the_swing() {
+    let mut sim = swinging(Vector2::new(200.0, 0.0));
+    let reel_in = InputState { reel: 1.0, ..Default::default() };
+
+    sim.step(DT, &reel_in);
+    assert!(sim.player.grappler.length < 100.0);
+    assert!(sim.player.velocity.length() > 200.0);
+}
+
+#[test]
+fn a_full_reel_in_never_passes_the_swing_speed_cap() {
+    // the longest rope pulled all the way in while already moving fast
+    let mut sim = swinging(Vector2::new(300.0, 0.0));
+    sim.player.grappler.grapple_point = Vector2::new(0.0, -400.0);
+    sim.player.grappler.length = 400.0;
+    sim.player.grappler.state = GrapplerState::Pulling;
+
+    let mut fastest: f32 = 0.0;
+    for _ in 0..120 {
+        sim.step(DT, &InputState::default());
+        if sim.player.grappler.state == GrapplerState::Idle {
+            break;
+        }
+        fastest = fastest.max(sim.player.velocity.length());
+    }
+    assert_eq!(sim.player.grappler.state, GrapplerState::Idle, "pulled all the way in");
+    // gravity can add a single tick on top of the cap
+    assert!(fastest <= MAX_SWING_SPEED + 20.0, "reached {}", fastest);
+    assert!(sim.player.velocity.length() <= MAX_SWING_SPEED + 100.0, "let go at {:?}", sim.player.velocity);
+
+    // same with the analog reel down to the shortest rope
+    let mut sim = swinging(Vector2::new(300.0, 0.0));
+    sim.player.grappler.grapple_point = Vector2::new(0.0, -400.0);
+    sim.player.grappler.length = 400.0;
+    let reel_in = InputState { reel: 1.0, ..Default::default() };
+    let mut fastest: f32 = 0.0;
+    for _ in 0..120 {
+        sim.step(DT, &reel_in);
+        fastest = fastest.max(sim.player.velocity.length());
+    }
+    assert!(sim.player.grappler.length <= 30.0 + 1e-3);
+    assert!(fastest <= MAX_SWING_SPEED + 20.0, "reached {}", fastest);
+}
+
+#[test]
+fn reeling_out_stops_at_rope_length() {
+    let mut sim = swinging(Vector2::new(0.0, 0.0));
+    let reel_out = InputState { reel: -1.0, ..Default::default() };
+
+    sim.run(2.0, DT, &reel_out);
+    let max = sim.player.grappler.stats().rope_length;
+    assert!((sim.player.grappler.length - max).abs() < 1e-3);
+}
+
+#[test]
+fn coyote_time_allows_a_late_jump() {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), Vec::new());
+    // grounded last frame, then walked off the edge
+    sim.step(DT, &InputState::default());
+    assert!(!sim.player.grounded);
+
+    sim.step(DT, &jump_pressed());
+    assert!(sim.player.velocity.y < -250.0);
+}
+
+#[test]
+fn no_jump_after_coyote_time() {
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), Vec::new());
+    sim.run(0.3, DT, &InputState::default());
+
+    sim.step(DT, &jump_pressed());
+    assert!(sim.player.velocity.y > 0.0);
+}
+
+#[test]
+fn buffered_jump_fires_on_landing() {
+    let mut sim = standing();
+    sim.player.position.y = -8.0;
+    sim.player.velocity.y = 200.0;
+    sim.player.grounded = false;
+
+    // pressed just before touching down, then only held
+    sim.step(DT, &jump_pressed());
+    let mut jumped = false;
+    for _ in 0..5 {
+        sim.step(DT, &jump_held());
+        jumped |= sim.player.velocity.y < -200.0;
+    }
+    assert!(jumped);
+}
+
+#[test]
+fn holding_jump_does_not_repeat_it() {
+    let mut sim = standing();
+    sim.step(DT, &jump_pressed());
+    sim.run(2.0, DT, &jump_held());
+    assert!(sim.player.grounded);
+    assert!(sim.player.velocity.y >= 0.0);
+}
+
+#[test]
+fn releasing_jump_early_cuts_it_short() {
+    let mut held = standing();
+    held.step(DT, &jump_pressed());
+    held.step(DT, &jump_held());
+
+    let mut cut = standing();
+    cut.step(DT, &jump_pressed());
+    cut.step(DT, &InputState::default());
+
+    assert!(cut.player.velocity.y > held.player.velocity.y * 0.6);
+}
+
+#[test]
+fn double_jump_needs_the_upgrade_and_works_once() {
+    let mut sim = falling();
+    sim.step(DT, &jump_pressed());
+    assert!(sim.player.velocity.y > 0.0);
+
+    sim.player.can_double_jump = true;
+    sim.step(DT, &InputState::default());
+    sim.step(DT, &jump_pressed());
+    assert!(sim.player.velocity.y < -200.0);
+
+    sim.run(1.0, DT, &InputState::default());
+    sim.step(DT, &jump_pressed());
+    assert!(sim.player.velocity.y > 0.0);
+}
+
+#[test]
+fn air_dash_moves_sideways_once() {
+    let mut sim = falling();
+    sim.player.can_dash = true;
+    let dash = InputState { dash: true, move_x: -1.0, ..Default::default() };
+
+    sim.step(DT, &dash);
+    let physics = sim.player.physics;
+    assert!((sim.player.velocity.x + physics.dash_speed).abs() < 1e-3);
+    assert_eq!(sim.player.velocity.y, 0.0);
+
+    sim.run(0.5, DT, &InputState::default());
+    assert_eq!(sim.player.velocity.x, -physics.move_speed);
+    sim.step(DT, &dash);
+    assert_eq!(sim.player.velocity.x, -physics.move_speed);
+}
+
+// tall wall just to the right of the player, who starts in the air
+fn beside_wall() -> Simulation {
+    let wall = Object::new(Vector2::new(20.0, -2000.0), Vector2::new(100.0, 4000.0));
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), vec![wall]);
+    sim.player.grounded = false;
+    sim
+}
+
+fn push_right() -> InputState {
+    InputState { move_x: 1.0, ..Default::default() }
+}
+
+fn touch_wall(sim: &mut Simulation) {
+    for _ in 0..60 {
+        sim.step(DT, &push_right());
+        if sim.player.wall.is_some() {
+            return;
+        }
+    }
+    panic!("never reached the wall");
+}
+
+fn wall_jump_pressed() -> InputState {
+    InputState { move_x: 1.0, ..jump_pressed() }
+}
+
+#[test]
+fn wall_contact_knows_its_side() {
+    let mut sim = beside_wall();
+    touch_wall(&mut sim);
+    let wall = sim.player.wall.unwrap();
+    assert_eq!(wall.side, WallSide::Right);
+    assert_eq!(wall.x, 20.0);
+}
+
+#[test]
+fn wall_slide_caps_fall_speed() {
+    let mut sim = beside_wall();
+    sim.player.velocity.y = 400.0;
+    touch_wall(&mut sim);
+    assert!(sim.player.velocity.y <= sim.player.physics.wall_slide_speed);
+}
+
+#[test]
+fn wall_jump_kicks_away_from_the_wall() {
+    let mut sim = beside_wall();
+    touch_wall(&mut sim);
+
+    sim.step(DT, &wall_jump_pressed());
+    assert!(sim.player.velocity.x < -150.0);
+    assert!(sim.player.velocity.y < -200.0);
+}
+
+#[test]
+fn same_wall_only_takes_a_few_jumps() {
+    let mut sim = beside_wall();
+    let max = sim.player.physics.max_same_wall_jumps;
+
+    for _ in 0..max {
+        touch_wall(&mut sim);
+        sim.step(DT, &wall_jump_pressed());
+        assert!(sim.player.velocity.y < -200.0);
+    }
+
+    touch_wall(&mut sim);
+    sim.step(DT, &wall_jump_pressed());
+    assert!(sim.player.velocity.y > -200.0);
+}
+
+#[test]
+fn landing_on_a_hazard_counts_as_a_fall() {
+    let spikes = Object::new(Vector2::new(-500.0, 10.0), Vector2::new(1000.0, 100.0)).with_kind(ObjectKind::Hazard);
+    let mut sim = Simulation::new(Vector2::new(0.0, 0.0), vec![spikes]);
+    let update = sim.step(DT, &InputState::default());
+    assert!(update.fell);
+}
+
+#[test]
+fn grapple_slips_off_unhookable_objects() {
+    let ceiling = Object::new(Vector2::new(-500.0, -150.0), Vector2::new(1000.0, 20.0));
+    let aim_up = InputState { fire: true, aim_target: Vector2::new(0.0, -200.0), ..Default::default() };
+
+    let mut sim = falling();
+    sim.objects.push(ceiling);
+    sim.step(DT, &aim_up);
+    sim.run(0.3, DT, &InputState::default());
+    assert_eq!(sim.player.grappler.state, GrapplerState::Grappled);
+
+    let mut sim = falling();
+    sim.objects.push(ceiling.with_kind(ObjectKind::Unhookable));
+    sim.step(DT, &aim_up);
+    sim.run(0.3, DT, &InputState::default());
+    assert_ne!(sim.player.grappler.state, GrapplerState::Grappled);
+}
+
+#[test]
+fn aim_assist_locks_onto_the_nearest_corner_in_reach() {
+    // looking down on two cubes, only the near one is within rope range
+    let near = Object::new(Vector2::new(40.0, 200.0), Vector2::new(50.0, 50.0));
+    let far = Object::new(Vector2::new(0.0, 900.0), Vector2::new(50.0, 50.0));
+    let objects = [far, near];
+    let aim = Vector2::new(0.0, 300.0);
+
+    let target = find_aim_target(Vector2::zero(), aim, &objects, 1.0, 400.0).unwrap();
+    assert_eq!((target.point.x, target.point.y), (40.0, 200.0));
+    assert!(find_aim_target(Vector2::zero(), aim, &objects, 0.0, 400.0).is_none());
+    assert!(find_aim_target(Vector2::zero(), aim, &[far], 1.0, 400.0).is_none());
+}
+
+#[test]
+fn aim_assist_notices_cubes_coming_and_going() {
+    // the player doesn't move, only the world around them does
+    let mut grappler = Grappler::new(Vector2::zero());
+    grappler.aim_assist = 1.0;
+    let aim = Vector2::new(0.0, 300.0);
+    let cube = Object::new(Vector2::new(40.0, 200.0), Vector2::new(50.0, 50.0));
+
+    grappler.update_aim(Vector2::zero(), aim, &[]);
+    assert!(grappler.aim_target.is_none());
+    grappler.update_aim(Vector2::zero(), aim, &[cube]);
+    assert!(grappler.aim_target.is_some());
+    assert!(grappler.predicted_hit.is_some());
+    grappler.update_aim(Vector2::zero(), aim, &[]);
+    assert!(grappler.aim_target.is_none());
+    assert!(grappler.predicted_hit.is_none());
+}
+
+#[test]
+fn upgrades_unlock_after_their_parent() {
+    for (i, upgrade) in UPGRADE_TREE.iter().enumerate() {
+        if let Some(parent) = node(*upgrade).requires {
+            let parent_index = UPGRADE_TREE.iter().position(|other| *other == parent).unwrap();
+            assert!(parent_index < i, "{:?} is listed before its parent", upgrade);
+            assert!(node(parent).unlock_height < node(*upgrade).unlock_height);
+        }
+    }
+
+    let mut unlocks = Unlocks::default();
+    assert!(unlocks.unlock_by_height(100.0).is_empty());
+    assert_eq!(unlocks.unlock_by_height(500.0), vec![Upgrade::LongerRope]);
+    // a parent and its child can open up in the same climb
+    let new = unlocks.unlock_by_height(1500.0);
+    assert!(new.contains(&Upgrade::LongHook));
+    assert!(!new.contains(&Upgrade::LongerRope));
+    assert!(unlocks.unlock_by_height(1500.0).is_empty());
+}
+
+#[test]
+fn unlocked_hooks_are_handed_to_the_player() {
+    let mut sim = standing();
+    let unlocks = Unlocks { unlocked: vec![Upgrade::LongerRope, Upgrade::LongHook] };
+    sim.player.grappler.hook = HookType::Bungee;
+    unlocks.apply(&mut sim.player);
+
+    assert_eq!(sim.player.grappler.available_hooks, vec![HookType::Standard, HookType::Long]);
+    assert_eq!(sim.player.grappler.hook, HookType::Standard);
+    assert!(sim.player.grappler.stats().rope_length > Unlocks::default().stats().rope_length);
+}
+
+#[test]
+fn long_hook_reaches_a_ceiling_the_standard_one_misses() {
+    let ceiling = Object::new(Vector2::new(-500.0, -520.0), Vector2::new(1000.0, 20.0));
+    for (hook, reaches) in [(HookType::Standard, false), (HookType::Long, true)] {
+        // hanging still in the air so the only difference is the reach
+        let mut sim = falling();
+        sim.player.physics.gravity = 0.0;
+        sim.objects.push(ceiling);
+        sim.player.grappler.hook = hook;
+        sim.step(DT, &fire_at(Vector2::new(0.0, -200.0)));
+        sim.run(1.0, DT, &InputState::default());
+        assert_eq!(sim.player.grappler.state == GrapplerState::Grappled, reaches, "{:?}", hook);
+    }
+}
+
+#[test]
+fn double_hook_spends_its_second_shot_on_another_anchor() {
+    let ceiling = Object::new(Vector2::new(-500.0, -150.0), Vector2::new(1000.0, 20.0));
+    let wall = Object::new(Vector2::new(150.0, -500.0), Vector2::new(20.0, 1000.0));
+    let mut sim = falling();
+    sim.objects.extend([ceiling, wall]);
+    sim.player.grappler.hook = HookType::Double;
+    sim.step(DT, &fire_at(Vector2::new(0.0, -200.0)));
+    sim.run(0.3, DT, &InputState::default());
+    assert_eq!(sim.player.grappler.state, GrapplerState::Grappled);
+
+    sim.step(DT, &fire_at(Vector2::new(200.0, 0.0)));
+    assert_eq!(sim.player.grappler.state, GrapplerState::Grappled);
+    let second = sim.player.grappler.second_point.unwrap();
+    assert!((second.x - 150.0).abs() < 6.0, "{:?}", second);
+
+    // with both anchors set the next press reels in like the standard hook
+    sim.step(DT, &fire_at(Vector2::new(200.0, 0.0)));
+    assert_eq!(sim.player.grappler.state, GrapplerState::Pulling);
+}
+
+#[test]
+fn bungee_hook_flings_you_instead_of_pulling_you_to_the_anchor() {
+    for hook in [HookType::Standard, HookType::Bungee] {
+        let mut sim = swinging(Vector2::zero());
+        sim.player.grappler.hook = hook;
+        sim.player.grappler.state = GrapplerState::Pulling;
+        for _ in 0..120 {
+            sim.step(DT, &InputState::default());
+            if sim.player.grappler.state == GrapplerState::Idle {
+                break;
+            }
+        }
+        assert_eq!(sim.player.grappler.state, GrapplerState::Idle, "{:?}", hook);
+        let at_anchor = sim.player.position.distance_to(Vector2::new(0.0, -100.0)) < 1.0;
+        assert_eq!(at_anchor, hook == HookType::Standard, "{:?} ended at {:?}", hook, sim.player.position);
+        if hook == HookType::Bungee {
+            assert!(sim.player.velocity.y < 0.0, "{:?}", sim.player.velocity);
+        }
+    }
+}
+
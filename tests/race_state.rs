@@ -0,0 +1,34 @@
+use darkswing::input::MenuInput;
+use darkswing::race_state::{COUNTDOWN, RaceAction, RaceState};
+
+#[test]
+fn the_countdown_runs_into_the_race() {
+    let mut state = RaceState::new();
+    assert_eq!(state.update(COUNTDOWN / 2.0, false, None), RaceAction::Continue);
+    assert_eq!(state, RaceState::Countdown(COUNTDOWN / 2.0));
+    state.update(COUNTDOWN / 2.0, false, None);
+    assert_eq!(state, RaceState::Racing);
+}
+
+#[test]
+fn escape_shows_the_results_instead_of_leaving() {
+    // the same escape press comes in as both the menu request and back
+    let mut state = RaceState::Racing;
+    assert_eq!(state.update(0.1, true, Some(MenuInput::Back)), RaceAction::Continue);
+    assert_eq!(state, RaceState::Results);
+
+    let mut state = RaceState::new();
+    assert_eq!(state.update(0.1, true, Some(MenuInput::Back)), RaceAction::Continue);
+    assert_eq!(state, RaceState::Results);
+
+    // a later press leaves
+    assert_eq!(state.update(0.1, true, Some(MenuInput::Back)), RaceAction::Leave);
+}
+
+#[test]
+fn confirming_the_results_starts_another_race() {
+    let mut state = RaceState::Results;
+    assert_eq!(state.update(0.1, false, None), RaceAction::Continue);
+    assert_eq!(state.update(0.1, false, Some(MenuInput::Confirm)), RaceAction::Restart);
+    assert_eq!(state, RaceState::Countdown(COUNTDOWN));
+}
@@ -0,0 +1,47 @@
+use darkswing::player::Player;
+use darkswing::save::SaveData;
+use darkswing::shop::{Purchase, ShopItem, Skin, buy};
+use darkswing::upgrades::Upgrade;
+use raylib::prelude::*;
+
+#[test]
+fn skins_cost_shards_and_are_worn_when_bought() {
+    let mut save = SaveData { shards: 20, ..Default::default() };
+    assert_eq!(buy(&mut save, ShopItem::Skin(Skin::Gold)), Purchase::TooExpensive);
+    assert_eq!(buy(&mut save, ShopItem::Skin(Skin::Crimson)), Purchase::Bought);
+    assert_eq!(save.shards, 5);
+    assert_eq!(save.wardrobe.equipped, Skin::Crimson);
+
+    // owned skins are free to switch between
+    assert_eq!(buy(&mut save, ShopItem::Skin(Skin::Classic)), Purchase::Equipped);
+    assert_eq!(buy(&mut save, ShopItem::Skin(Skin::Crimson)), Purchase::Equipped);
+    assert_eq!(buy(&mut save, ShopItem::Skin(Skin::Crimson)), Purchase::Owned);
+    assert_eq!(save.shards, 5);
+
+    let mut player = Player::new(Vector2::zero());
+    save.wardrobe.apply(&mut player);
+    let (worn, crimson) = (player.color, Skin::Crimson.color());
+    assert_eq!((worn.r, worn.g, worn.b), (crimson.r, crimson.g, crimson.b));
+}
+
+#[test]
+fn upgrades_need_their_parent_first() {
+    let mut save = SaveData { shards: 100, ..Default::default() };
+    assert_eq!(buy(&mut save, ShopItem::Upgrade(Upgrade::LongHook)), Purchase::Locked);
+    assert_eq!(buy(&mut save, ShopItem::Upgrade(Upgrade::LongerRope)), Purchase::Bought);
+    assert_eq!(buy(&mut save, ShopItem::Upgrade(Upgrade::LongHook)), Purchase::Bought);
+    assert_eq!(buy(&mut save, ShopItem::Upgrade(Upgrade::LongHook)), Purchase::Owned);
+    assert_eq!(save.shards, 100 - 5 - 15);
+
+    // climbing past the height later doesn't unlock it twice
+    let unlocked = save.unlocks.unlock_by_height(1500.0);
+    assert!(!unlocked.contains(&Upgrade::LongerRope) && !unlocked.contains(&Upgrade::LongHook));
+}
+
+#[test]
+fn every_skin_and_upgrade_is_for_sale() {
+    let items = ShopItem::all();
+    assert_eq!(items.len(), Skin::ALL.len() + 8);
+    assert_eq!(items[0].price(), 0);
+    assert!(items.iter().skip(1).all(|item| item.price() > 0));
+}
@@ -0,0 +1,77 @@
+use darkswing::input::InputState;
+use darkswing::save::SaveData;
+use darkswing::speedrun::{SplitRecord, Speedrun, format_delta, format_time};
+
+mod common;
+use common::{moving};
+
+fn record(times: &[f32]) -> SplitRecord {
+    SplitRecord { interval: 1000.0, times: times.to_vec() }
+}
+
+#[test]
+fn splits_are_taken_at_each_milestone() {
+    let mut run = Speedrun::new(1000.0, None);
+    assert_eq!(run.update(1.0, &InputState::default(), 0.0), None);
+    assert!(!run.started);
+
+    assert_eq!(run.update(1.0, &moving(), 999.0), None);
+    assert_eq!(run.update(1.0, &moving(), 1000.0), Some(0));
+    // staying at the same height doesn't split again
+    assert_eq!(run.update(1.0, &moving(), 1500.0), None);
+    assert_eq!(run.update(1.0, &moving(), 2100.0), Some(1));
+    assert_eq!(run.splits, vec![2.0, 4.0]);
+    assert_eq!(run.next_milestone(), 3000.0);
+}
+
+#[test]
+fn splits_compare_against_the_personal_best() {
+    let mut run = Speedrun::new(1000.0, Some(record(&[3.0, 5.0])));
+    run.update(2.0, &moving(), 1000.0);
+    run.update(4.0, &moving(), 2000.0);
+    run.update(1.0, &moving(), 3000.0);
+
+    assert_eq!(run.delta(0), Some(-1.0));
+    assert_eq!(run.delta(1), Some(1.0));
+    // nothing to compare the third split to
+    assert_eq!(run.delta(2), None);
+    assert_eq!(format_delta(-1.0), "-1.00");
+    assert_eq!(format_time(65.5), "1:05.50");
+
+    // a best from another interval isn't used
+    let other = Speedrun::new(500.0, Some(record(&[3.0])));
+    assert!(other.personal_best.is_none());
+}
+
+#[test]
+fn only_a_better_run_replaces_the_personal_best() {
+    // more milestones wins, then the faster last split
+    assert!(record(&[2.0, 9.0]).beats(&record(&[1.0])));
+    assert!(record(&[2.0, 4.0]).beats(&record(&[1.0, 5.0])));
+    assert!(!record(&[1.0, 6.0]).beats(&record(&[2.0, 5.0])));
+
+    let mut save = SaveData::default();
+    assert!(!save.record_splits(7, &record(&[])));
+    assert!(save.record_splits(7, &record(&[3.0])));
+    assert!(!save.record_splits(7, &record(&[4.0])));
+    assert!(save.record_splits(7, &record(&[2.5])));
+    assert_eq!(save.splits[&7].times, vec![2.5]);
+}
+
+#[test]
+fn export_writes_one_row_per_split() {
+    let mut run = Speedrun::new(1000.0, Some(record(&[3.0])));
+    run.update(2.0, &moving(), 1000.0);
+    run.update(2.0, &moving(), 2000.0);
+
+    let path = std::env::temp_dir().join(format!("darkswing_splits_{}.csv", std::process::id()));
+    run.export(path.to_str().unwrap(), 42);
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "# darkswing splits, seed 42, every 1000 units");
+    assert_eq!(lines[1], "split,height,time,pb_time,delta");
+    assert_eq!(lines[2], "1,1000,2.000,3.000,-1.000");
+    assert_eq!(lines[3], "2,2000,4.000,,");
+}
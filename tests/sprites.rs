@@ -0,0 +1,104 @@
+use darkswing::grappler::GrapplerState;
+use darkswing::player::{Player, WallContact, WallSide};
+use darkswing::sprites::{ATLAS_PATH, Animator, AtlasDescriptor, PlayerAnimation};
+use raylib::prelude::*;
+
+const DESCRIPTOR: &str = r#"{
+  "texture": "atlas.png",
+  "frames": {
+    "a": [0, 0, 16, 24],
+    "b": [16, 0, 16, 24],
+    "c": [32, 0, 16, 24],
+    "box": [0, 24, 24, 24]
+  },
+  "animations": {
+    "loop": { "frames": ["a", "b", "c"], "fps": 10 },
+    "once": { "frames": ["a", "b", "c"], "fps": 10, "looping": false }
+  },
+  "nine_slices": {
+    "object_solid": { "frame": "box", "border": 4 }
+  }
+}"#;
+
+fn airborne() -> Player {
+    let mut player = Player::new(Vector2::zero());
+    player.grounded = false;
+    player
+}
+
+#[test]
+fn descriptor_parses_frames_animations_and_slices() {
+    let desc = AtlasDescriptor::parse(DESCRIPTOR).unwrap();
+    assert_eq!(desc.texture, "atlas.png");
+    assert_eq!(desc.frame("b"), Some(Rectangle::new(16.0, 0.0, 16.0, 24.0)));
+    assert_eq!(desc.frame("missing"), None);
+    assert!(desc.animations["loop"].looping);
+    assert_eq!(desc.nine_slices["object_solid"].border, 4);
+}
+
+#[test]
+fn unknown_frames_are_rejected() {
+    let text = DESCRIPTOR.replace(r#"["a", "b", "c"], "fps": 10 }"#, r#"["a", "nope"], "fps": 10 }"#);
+    let err = AtlasDescriptor::parse(&text).unwrap_err();
+    assert!(err.contains("nope"), "{}", err);
+
+    let text = DESCRIPTOR.replace(r#""frame": "box""#, r#""frame": "gone""#);
+    assert!(AtlasDescriptor::parse(&text).is_err());
+}
+
+#[test]
+fn looping_animations_wrap_and_others_hold_the_last_frame() {
+    let desc = AtlasDescriptor::parse(DESCRIPTOR).unwrap();
+    let looping = &desc.animations["loop"];
+    let once = &desc.animations["once"];
+    assert_eq!(looping.frame_at(0.0), "a");
+    assert_eq!(looping.frame_at(0.15), "b");
+    assert_eq!(looping.frame_at(0.35), "a");
+    assert_eq!(once.frame_at(0.35), "c");
+    assert_eq!(once.frame_at(10.0), "c");
+}
+
+#[test]
+fn shipped_atlas_has_every_player_animation() {
+    let desc = AtlasDescriptor::parse(&std::fs::read_to_string(ATLAS_PATH).unwrap()).unwrap();
+    for animation in [
+        PlayerAnimation::Idle,
+        PlayerAnimation::Run,
+        PlayerAnimation::Jump,
+        PlayerAnimation::Swing,
+        PlayerAnimation::WallSlide,
+    ] {
+        assert!(desc.animations.contains_key(animation.name()), "{}", animation.name());
+    }
+}
+
+#[test]
+fn animation_follows_player_state() {
+    let mut player = Player::new(Vector2::zero());
+    assert_eq!(PlayerAnimation::from_player(&player), PlayerAnimation::Idle);
+    player.velocity.x = 100.0;
+    assert_eq!(PlayerAnimation::from_player(&player), PlayerAnimation::Run);
+
+    let mut player = airborne();
+    assert_eq!(PlayerAnimation::from_player(&player), PlayerAnimation::Jump);
+    player.wall = Some(WallContact { side: WallSide::Left, x: -10.0 });
+    assert_eq!(PlayerAnimation::from_player(&player), PlayerAnimation::WallSlide);
+    // the rope wins over the wall
+    player.grappler.state = GrapplerState::Grappled;
+    assert_eq!(PlayerAnimation::from_player(&player), PlayerAnimation::Swing);
+}
+
+#[test]
+fn animator_restarts_when_the_animation_changes() {
+    let mut player = Player::new(Vector2::zero());
+    let mut animator = Animator::new();
+    animator.update(0.5, &player);
+    animator.update(0.5, &player);
+    assert_eq!(animator.animation, PlayerAnimation::Idle);
+    assert_eq!(animator.time, 1.0);
+
+    player.grounded = false;
+    animator.update(0.5, &player);
+    assert_eq!(animator.animation, PlayerAnimation::Jump);
+    assert_eq!(animator.time, 0.0);
+}
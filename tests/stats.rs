@@ -0,0 +1,84 @@
+use darkswing::achievements::{Achievement, Achievements};
+use darkswing::events::GameEvent;
+use darkswing::grappler::GrapplerState;
+use darkswing::light::Visibility;
+use darkswing::player::Player;
+use darkswing::save::SaveData;
+use darkswing::stats::Stats;
+use raylib::prelude::*;
+
+#[test]
+fn hook_and_fall_events_are_counted() {
+    let mut stats = Stats::default();
+    assert_eq!(stats.accuracy(), None);
+
+    let position = Vector2::zero();
+    for _ in 0..4 {
+        stats.handle_event(&GameEvent::HookFired { position });
+    }
+    stats.handle_event(&GameEvent::HookAttached { point: Vector2::new(0.0, -100.0), origin: position });
+    stats.handle_event(&GameEvent::Fell { position });
+    stats.handle_event(&GameEvent::Jumped { position });
+
+    assert_eq!(stats.hooks_fired, 4);
+    assert_eq!(stats.hooks_hit, 1);
+    assert_eq!(stats.falls, 1);
+    assert_eq!(stats.accuracy(), Some(0.25));
+}
+
+#[test]
+fn swinging_counts_distance_speed_and_height() {
+    let mut stats = Stats::default();
+    let mut player = Player::new(Vector2::new(0.0, -1000.0));
+    player.velocity = Vector2::new(300.0, 400.0);
+
+    stats.update(1.0, &player, &Visibility::new(&player, Vec::new(), false));
+    assert_eq!(stats.distance_swung, 0.0, "not on a rope");
+    assert_eq!(stats.max_speed, 500.0);
+    assert_eq!(stats.highest_layer, 6);
+    assert_eq!(stats.time_in_darkness, 0.0, "darkness is off");
+
+    player.grappler.state = GrapplerState::Grappled;
+    player.velocity = Vector2::new(100.0, 0.0);
+    stats.update(0.5, &player, &Visibility::new(&player, Vec::new(), true));
+    assert_eq!(stats.distance_swung, 50.0);
+    assert_eq!(stats.max_speed, 500.0);
+    assert_eq!(stats.time_in_darkness, 0.5, "the caves are dark");
+
+    // a lamp overhead means you aren't in the dark
+    let lamp = vec![(player.position, 100.0)];
+    stats.update(0.5, &player, &Visibility::new(&player, lamp, true));
+    assert_eq!(stats.time_in_darkness, 0.5);
+}
+
+#[test]
+fn achievements_unlock_once() {
+    let mut achievements = Achievements::default();
+    let mut stats = Stats::default();
+    assert!(achievements.unlock_from(&stats).is_empty());
+
+    stats.hooks_fired = 1;
+    stats.hooks_hit = 1;
+    stats.highest_layer = 12;
+    assert_eq!(achievements.unlock_from(&stats), vec![Achievement::FirstBite, Achievement::OffTheGround]);
+    assert!(achievements.unlock_from(&stats).is_empty());
+    assert!(achievements.has(Achievement::FirstBite));
+    assert!(!achievements.has(Achievement::Sharpshooter));
+}
+
+#[test]
+fn stats_and_achievements_survive_a_save() {
+    let path = std::env::temp_dir().join(format!("darkswing_stats_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut save = SaveData::default();
+    save.stats.falls = 7;
+    save.stats.distance_swung = 1234.5;
+    save.achievements.unlocked.push(Achievement::Pendulum);
+    save.save(path);
+
+    let loaded = SaveData::load(path);
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded.stats.falls, 7);
+    assert_eq!(loaded.stats.distance_swung, 1234.5);
+    assert!(loaded.achievements.has(Achievement::Pendulum));
+}